@@ -1,22 +1,20 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     fs::{self, File},
-    hash::BuildHasherDefault,
-    io::{self, BufRead, BufReader, Lines},
-    iter::Flatten,
+    io::{self, BufRead},
     path::Path,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use crate::loading::{DataType, Episode, Peep, Principal, Rating, Show};
-use nohash_hasher::{NoHashHasher};
+use crate::{
+    index,
+    loading::{DataType, Episode, Peep, Principal, Rating, Show},
+};
 use rusqlite::{params, Connection, Statement};
 
-type FastMap<T> = HashMap<u64, T, BuildHasherDefault<NoHashHasher<u64>>>;
-
-fn read_lines<D: DataType>(filename: &str) -> Flatten<Lines<BufReader<File>>> {
+fn read_lines<D: DataType>(filename: &str) -> impl Iterator<Item = String> {
     let file = File::open(filename).expect("File should exist");
-    let mut lines = io::BufReader::new(file).lines().flatten();
+    let mut lines = io::BufReader::new(file).lines().map_while(Result::ok);
 
     if !D::mapping_ok(&lines.next().expect("File should contain header")) {
         panic!("File {filename} does not match expected header");
@@ -53,16 +51,60 @@ fn index_data<D: DataType>(
     ids
 }
 
-pub fn create() {
-    let db_path = Path::new("caterer.db");
-    _ = fs::remove_file(db_path);
-    while db_path.exists() {
-        std::thread::sleep(Duration::from_millis(50));
+/// The stamp written at the end of `create`, used to tie an exported index to
+/// the exact database it was built from.
+pub fn build_stamp(db: &Connection) -> Option<i64> {
+    db.query_row(
+        "SELECT value FROM meta WHERE key = 'build_stamp';",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Whether the database has the `votes` column, which it doesn't when it's
+/// from before vote counts.
+pub fn has_votes(db: &Connection) -> bool {
+    db.prepare("SELECT votes FROM shows LIMIT 0;").is_ok()
+}
+
+/// Brings a `caterer.db` from before vote counts up to date by adding the
+/// `votes` column, filled in from `title.ratings.tsv` if it's still here.
+/// Without it every vote count stays unknown until the next `build`. Only
+/// `export-index` runs this, searches never write to the database.
+pub fn upgrade(db: &Connection) {
+    if has_votes(db) {
+        return;
     }
 
-    let mut db = Connection::open(db_path).expect("can create db");
-    db.pragma_update(None, "foreign_keys", "ON").unwrap();
+    db.execute("ALTER TABLE shows ADD COLUMN votes INTEGER;", [])
+        .expect("caterer.db should be writable to add vote counts");
+    let filename = "title.ratings.tsv";
+    if !Path::new(filename).exists() {
+        eprintln!(
+            "----> caterer.db has no vote counts and there's no {filename} to fill them in, rebuild with `build`"
+        );
+        return;
+    }
 
+    eprintln!("----> Adding vote counts to caterer.db from {filename}");
+    let tx = db.unchecked_transaction().unwrap();
+    {
+        let mut statement = tx
+            .prepare("UPDATE shows SET votes = ?1 WHERE id = ?2")
+            .unwrap();
+        for line in read_lines::<Rating>(filename) {
+            let rating = Rating::load(&line);
+            statement
+                .execute(params![&rating.votes, &rating.show_id])
+                .expect("sqlite should be healthy");
+        }
+    }
+    tx.commit().unwrap();
+}
+
+/// The empty schema `create` loads the IMDb files into.
+pub fn create_tables(db: &Connection) {
     db.execute_batch(
         "BEGIN;
         CREATE TABLE shows (
@@ -72,6 +114,7 @@ pub fn create() {
             start_year     TEXT,
             genres         TEXT,
             rating         TEXT,
+            votes          INTEGER,
             parent_show_id INTEGER,
             FOREIGN KEY (parent_show_id) REFERENCES shows(id)
         );
@@ -89,12 +132,29 @@ pub fn create() {
             FOREIGN KEY (peep_id) REFERENCES peeps(id),
             FOREIGN KEY (show_id) REFERENCES shows(id)
         );
+        CREATE TABLE meta (
+            key    TEXT PRIMARY KEY,
+            value  INTEGER
+        );
         CREATE INDEX idx_parent_show_id ON shows(parent_show_id);
         CREATE INDEX idx_principals_show_id ON principals(show_id);
         CREATE INDEX idx_principals_peep_id ON principals(peep_id);
         COMMIT;",
     )
     .expect("sqlite tables should create ok");
+}
+
+pub fn create() {
+    let db_path = Path::new("caterer.db");
+    _ = fs::remove_file(db_path);
+    while db_path.exists() {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let mut db = Connection::open(db_path).expect("can create db");
+    db.pragma_update(None, "foreign_keys", "ON").unwrap();
+
+    create_tables(&db);
 
     println!("db created ok");
 
@@ -131,14 +191,14 @@ pub fn create() {
     index_data::<Rating>(
         &mut db,
         "title.ratings.tsv",
-        "UPDATE shows SET rating = ?1 WHERE id = ?2",
+        "UPDATE shows SET rating = ?1, votes = ?2 WHERE id = ?3",
         |statement, rating| {
             if !show_ids.contains(&rating.show_id) {
                 return;
-            };
+            }
 
             statement
-                .execute(params![&rating.rating, &rating.show_id])
+                .execute(params![&rating.rating, &rating.votes, &rating.show_id])
                 .expect("sqlite should be healthy");
         },
     );
@@ -161,7 +221,7 @@ pub fn create() {
         |statement, principal| {
             if !show_ids.contains(&principal.show_id) || !peep_ids.contains(&principal.peep_id) {
                 return;
-            };
+            }
 
             statement
                 .execute(params![
@@ -176,6 +236,18 @@ pub fn create() {
 
     println!("Loaded the files!");
 
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after the epoch")
+        .as_nanos() as i64;
+    db.execute(
+        "INSERT INTO meta (key, value) VALUES ('build_stamp', ?1)",
+        [stamp],
+    )
+    .expect("sqlite should be healthy");
+
+    index::export(&db);
+
     // println!("{:?}", basics.iter().next());
     // println!("{:?}", episodes.iter().next());
     // println!("{:?}", principals.iter().next());
@@ -0,0 +1,656 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::Instant,
+};
+
+use memmap2::Mmap;
+use rusqlite::Connection;
+
+use crate::db;
+
+pub const INDEX_PATH: &str = "caterer.idx";
+
+const MAGIC: &[u8; 8] = b"CATERIDX";
+const VERSION: u64 = 2;
+
+const HEADER_LEN: usize = 88;
+const SHOW_ROW: usize = 72;
+const PEEP_ROW: usize = 24;
+const ENTRY_ROW: usize = 16;
+const STR_REF: usize = 8;
+
+const NO_STR: u32 = u32::MAX;
+const NO_VOTES: i64 = -1;
+
+// File layout, all little-endian, each section padded to 8 bytes:
+//
+//   header      magic, version, build stamp, then the length of every section
+//   shows       id, title, title_type, start_year, genres, rating,
+//               entries start/len, episodes start/len, votes (sorted by id)
+//   peeps       id, name, shows start/len (sorted by id)
+//   entries     peep index, episode count, job codes start/len, root credit
+//   job codes   u32 indexes into the job table
+//   episodes    i64 episode ids
+//   peep shows  u32 indexes into the show table
+//   jobs        string refs for each distinct category/job
+//   strings     utf8 blob that every string ref points into
+struct Sections {
+    shows: usize,
+    peeps: usize,
+    entries: usize,
+    job_codes: usize,
+    episodes: usize,
+    peep_shows: usize,
+    jobs: usize,
+    strings: usize,
+}
+
+struct Counts {
+    shows: usize,
+    peeps: usize,
+    entries: usize,
+    job_codes: usize,
+    episodes: usize,
+    peep_shows: usize,
+    jobs: usize,
+    strings: usize,
+}
+
+fn pad(len: usize) -> usize {
+    (len + 7) & !7
+}
+
+impl Counts {
+    fn sections(&self) -> Sections {
+        let shows = HEADER_LEN;
+        let peeps = shows + pad(self.shows * SHOW_ROW);
+        let entries = peeps + pad(self.peeps * PEEP_ROW);
+        let job_codes = entries + pad(self.entries * ENTRY_ROW);
+        let episodes = job_codes + pad(self.job_codes * 4);
+        let peep_shows = episodes + pad(self.episodes * 8);
+        let jobs = peep_shows + pad(self.peep_shows * 4);
+        let strings = jobs + pad(self.jobs * STR_REF);
+        Sections {
+            shows,
+            peeps,
+            entries,
+            job_codes,
+            episodes,
+            peep_shows,
+            jobs,
+            strings,
+        }
+    }
+
+    fn file_len(&self) -> usize {
+        self.sections().strings + self.strings
+    }
+}
+
+#[derive(Default)]
+struct Strings {
+    blob: Vec<u8>,
+}
+
+impl Strings {
+    fn push(&mut self, s: Option<&str>) -> (u32, u32) {
+        let Some(s) = s else {
+            return (NO_STR, 0);
+        };
+        let off = self.blob.len() as u32;
+        self.blob.extend_from_slice(s.as_bytes());
+        (off, s.len() as u32)
+    }
+}
+
+struct ShowRow {
+    id: i64,
+    title: (u32, u32),
+    title_type: (u32, u32),
+    start_year: (u32, u32),
+    genres: (u32, u32),
+    rating: (u32, u32),
+    entries: (u32, u32),
+    episodes: (u32, u32),
+    votes: i64,
+}
+
+struct PeepRow {
+    id: i64,
+    name: (u32, u32),
+    shows: (u32, u32),
+}
+
+struct EntryRow {
+    peep: u32,
+    episode_count: u32,
+    jobs: (u32, u16),
+    root_credit: bool,
+}
+
+fn write_pair(out: &mut impl Write, (a, b): (u32, u32)) {
+    out.write_all(&a.to_le_bytes()).unwrap();
+    out.write_all(&b.to_le_bytes()).unwrap();
+}
+
+fn write_padding(out: &mut impl Write, len: usize) {
+    out.write_all(&vec![0; pad(len) - len]).unwrap();
+}
+
+/// Writes `caterer.idx` from a built database, so that a search can read
+/// every series and its crew straight out of a memory map.
+pub fn export(db: &Connection) {
+    export_to(db, Path::new(INDEX_PATH));
+}
+
+pub fn export_to(db: &Connection, path: &Path) {
+    let export_start = Instant::now();
+    println!("Exporting {}", path.display());
+
+    let stamp = db::build_stamp(db).expect("Database has no build stamp, rebuild it first");
+    let mut strings = Strings::default();
+
+    let mut shows: Vec<ShowRow> = vec![];
+    {
+        let mut show_q = db
+            .prepare(
+                "SELECT id, title, title_type, start_year, genres, rating, votes
+                FROM shows WHERE parent_show_id IS NULL ORDER BY id;",
+            )
+            .unwrap();
+        let mut rows = show_q.query([]).unwrap();
+        while let Some(row) = rows.next().unwrap() {
+            let text = |i| row.get::<_, Option<String>>(i).unwrap();
+            shows.push(ShowRow {
+                id: row.get(0).unwrap(),
+                title: strings.push(text(1).as_deref()),
+                title_type: strings.push(text(2).as_deref()),
+                start_year: strings.push(text(3).as_deref()),
+                genres: strings.push(text(4).as_deref()),
+                rating: strings.push(text(5).as_deref()),
+                entries: (0, 0),
+                episodes: (0, 0),
+                votes: row.get::<_, Option<i64>>(6).unwrap().unwrap_or(NO_VOTES),
+            });
+        }
+    }
+    let show_index = |id: i64| shows.binary_search_by_key(&id, |s| s.id).ok();
+    println!("  • {} shows", shows.len());
+
+    let mut episodes: Vec<i64> = vec![];
+    let mut episode_ranges: Vec<(usize, u32, u32)> = vec![];
+    {
+        let mut episode_q = db
+            .prepare(
+                "SELECT id, parent_show_id FROM shows
+                WHERE parent_show_id IS NOT NULL ORDER BY parent_show_id, id;",
+            )
+            .unwrap();
+        let mut rows = episode_q.query([]).unwrap();
+        let mut current: Option<(i64, u32)> = None;
+        while let Some(row) = rows.next().unwrap() {
+            let episode_id: i64 = row.get(0).unwrap();
+            let parent_id: i64 = row.get(1).unwrap();
+            if current.map(|(id, _)| id) != Some(parent_id) {
+                if let Some((id, start)) = current {
+                    if let Some(i) = show_index(id) {
+                        episode_ranges.push((i, start, episodes.len() as u32 - start));
+                    }
+                }
+                current = Some((parent_id, episodes.len() as u32));
+            }
+            episodes.push(episode_id);
+        }
+        if let Some((id, start)) = current {
+            if let Some(i) = show_index(id) {
+                episode_ranges.push((i, start, episodes.len() as u32 - start));
+            }
+        }
+    }
+    println!("  • {} episodes", episodes.len());
+
+    let mut peeps: Vec<PeepRow> = vec![];
+    {
+        let mut peep_q = db
+            .prepare("SELECT id, name FROM peeps ORDER BY id;")
+            .unwrap();
+        let mut rows = peep_q.query([]).unwrap();
+        while let Some(row) = rows.next().unwrap() {
+            peeps.push(PeepRow {
+                id: row.get(0).unwrap(),
+                name: strings.push(row.get::<_, Option<String>>(1).unwrap().as_deref()),
+                shows: (0, 0),
+            });
+        }
+    }
+    println!("  • {} people", peeps.len());
+
+    // Direct credits sort ahead of episode credits for the same person, which
+    // keeps job order identical to `fetch_show_record`.
+    let mut jobs: Vec<String> = vec![];
+    let mut job_codes_by_name: HashMap<String, u32> = HashMap::new();
+    let mut job_codes: Vec<u32> = vec![];
+    let mut entries: Vec<(usize, EntryRow)> = vec![];
+    {
+        let mut credit_q = db
+            .prepare(
+                "SELECT
+                    COALESCE(shows.parent_show_id, shows.id), principals.peep_id,
+                    principals.category, principals.job, shows.parent_show_id IS NULL
+                FROM principals
+                JOIN shows ON principals.show_id = shows.id
+                ORDER BY 1, 2, 5 DESC, principals.id;",
+            )
+            .unwrap();
+        let mut rows = credit_q.query([]).unwrap();
+        let mut current: Option<(i64, i64)> = None;
+        let mut entry_jobs: Vec<u32> = vec![];
+        let mut flush = |current: Option<(i64, i64)>,
+                         entry: &mut Option<EntryRow>,
+                         entry_jobs: &mut Vec<u32>| {
+            if let (Some((show_id, _)), Some(mut e)) = (current, entry.take()) {
+                if let Some(i) = show_index(show_id) {
+                    e.jobs = (job_codes.len() as u32, entry_jobs.len() as u16);
+                    job_codes.append(entry_jobs);
+                    entries.push((i, e));
+                }
+            }
+            entry_jobs.clear();
+        };
+        let mut entry: Option<EntryRow> = None;
+        while let Some(row) = rows.next().unwrap() {
+            let key = (row.get::<_, i64>(0).unwrap(), row.get::<_, i64>(1).unwrap());
+            if current != Some(key) {
+                flush(current, &mut entry, &mut entry_jobs);
+                current = Some(key);
+                entry = peeps
+                    .binary_search_by_key(&key.1, |p| p.id)
+                    .ok()
+                    .map(|peep| EntryRow {
+                        peep: peep as u32,
+                        episode_count: 0,
+                        jobs: (0, 0),
+                        root_credit: false,
+                    });
+            }
+            let Some(e) = entry.as_mut() else {
+                continue;
+            };
+
+            // A direct credit's category and job are kept as-is, and a later
+            // direct credit replaces an earlier one, same as the SQLite path.
+            let direct = row.get::<_, bool>(4).unwrap();
+            if direct {
+                e.root_credit = true;
+                entry_jobs.clear();
+            } else {
+                e.episode_count += 1;
+            }
+            for col in [2, 3] {
+                let Some(job) = row.get::<_, Option<String>>(col).unwrap() else {
+                    continue;
+                };
+                if job == "\\N" {
+                    continue;
+                }
+                let code = *job_codes_by_name.entry(job.clone()).or_insert_with(|| {
+                    jobs.push(job);
+                    jobs.len() as u32 - 1
+                });
+                if direct || !entry_jobs.contains(&code) {
+                    entry_jobs.push(code);
+                }
+            }
+        }
+        flush(current, &mut entry, &mut entry_jobs);
+    }
+    println!(
+        "  • {} credits over {} distinct jobs",
+        entries.len(),
+        jobs.len()
+    );
+
+    let mut show_entry_ranges: Vec<(usize, u32, u32)> = vec![];
+    for (n, (i, _)) in entries.iter().enumerate() {
+        match show_entry_ranges.last_mut() {
+            Some((last, _, len)) if last == i => *len += 1,
+            _ => show_entry_ranges.push((*i, n as u32, 1)),
+        }
+    }
+    for (i, start, len) in show_entry_ranges {
+        shows[i].entries = (start, len);
+    }
+    for (i, start, len) in episode_ranges {
+        shows[i].episodes = (start, len);
+    }
+
+    let mut peep_show_counts = vec![0u32; peeps.len()];
+    for (_, e) in &entries {
+        peep_show_counts[e.peep as usize] += 1;
+    }
+    let mut next = 0;
+    for (peep, count) in peeps.iter_mut().zip(&peep_show_counts) {
+        peep.shows = (next, 0);
+        next += count;
+    }
+    let mut peep_shows = vec![0u32; entries.len()];
+    for (show, e) in &entries {
+        let peep = &mut peeps[e.peep as usize];
+        peep_shows[(peep.shows.0 + peep.shows.1) as usize] = *show as u32;
+        peep.shows.1 += 1;
+    }
+
+    let job_refs: Vec<_> = jobs.iter().map(|j| strings.push(Some(j))).collect();
+
+    let counts = Counts {
+        shows: shows.len(),
+        peeps: peeps.len(),
+        entries: entries.len(),
+        job_codes: job_codes.len(),
+        episodes: episodes.len(),
+        peep_shows: peep_shows.len(),
+        jobs: job_refs.len(),
+        strings: strings.blob.len(),
+    };
+
+    let tmp_path = path.with_extension("idx.tmp");
+    let mut out = BufWriter::new(File::create(&tmp_path).expect("can create index"));
+
+    out.write_all(MAGIC).unwrap();
+    for value in [
+        VERSION,
+        stamp as u64,
+        counts.shows as u64,
+        counts.peeps as u64,
+        counts.entries as u64,
+        counts.job_codes as u64,
+        counts.episodes as u64,
+        counts.peep_shows as u64,
+        counts.jobs as u64,
+        counts.strings as u64,
+    ] {
+        out.write_all(&value.to_le_bytes()).unwrap();
+    }
+
+    for show in &shows {
+        out.write_all(&show.id.to_le_bytes()).unwrap();
+        for pair in [
+            show.title,
+            show.title_type,
+            show.start_year,
+            show.genres,
+            show.rating,
+            show.entries,
+            show.episodes,
+        ] {
+            write_pair(&mut out, pair);
+        }
+        out.write_all(&show.votes.to_le_bytes()).unwrap();
+    }
+    write_padding(&mut out, counts.shows * SHOW_ROW);
+
+    for peep in &peeps {
+        out.write_all(&peep.id.to_le_bytes()).unwrap();
+        write_pair(&mut out, peep.name);
+        write_pair(&mut out, peep.shows);
+    }
+    write_padding(&mut out, counts.peeps * PEEP_ROW);
+
+    for (_, e) in &entries {
+        write_pair(&mut out, (e.peep, e.episode_count));
+        out.write_all(&e.jobs.0.to_le_bytes()).unwrap();
+        out.write_all(&e.jobs.1.to_le_bytes()).unwrap();
+        out.write_all(&(e.root_credit as u16).to_le_bytes())
+            .unwrap();
+    }
+    write_padding(&mut out, counts.entries * ENTRY_ROW);
+
+    for code in &job_codes {
+        out.write_all(&code.to_le_bytes()).unwrap();
+    }
+    write_padding(&mut out, counts.job_codes * 4);
+
+    for episode in &episodes {
+        out.write_all(&episode.to_le_bytes()).unwrap();
+    }
+
+    for show in &peep_shows {
+        out.write_all(&show.to_le_bytes()).unwrap();
+    }
+    write_padding(&mut out, counts.peep_shows * 4);
+
+    for job in job_refs {
+        write_pair(&mut out, job);
+    }
+
+    out.write_all(&strings.blob).unwrap();
+    out.flush().unwrap();
+    drop(out);
+
+    fs::rename(&tmp_path, path).expect("can replace index");
+
+    let export_end = Instant::now().duration_since(export_start);
+    println!(
+        "Exported {} ({}MB) in {export_end:#?}",
+        path.display(),
+        counts.file_len() / 1_000_000
+    );
+}
+
+pub struct IndexedShow<'a> {
+    pub id: i64,
+    pub title: &'a str,
+    pub title_type: &'a str,
+    pub start_year: &'a str,
+    pub genres: &'a str,
+    pub rating: Option<&'a str>,
+    pub votes: Option<i64>,
+    pub episodes: Vec<i64>,
+    pub credits: Vec<IndexedCredit<'a>>,
+}
+
+pub struct IndexedMeta<'a> {
+    pub title_type: &'a str,
+    pub start_year: &'a str,
+    pub genres: &'a str,
+    pub rating: Option<&'a str>,
+    pub votes: Option<i64>,
+}
+
+pub struct IndexedCredit<'a> {
+    pub peep_id: i64,
+    pub name: &'a str,
+    pub jobs: Vec<&'a str>,
+    pub episode_count: usize,
+    pub root_credit: bool,
+}
+
+/// A read-only view over `caterer.idx`. Nothing is parsed up front, every
+/// lookup reads straight out of the memory map.
+pub struct Index {
+    map: Mmap,
+    stamp: i64,
+    counts: Counts,
+    sections: Sections,
+}
+
+impl Index {
+    pub fn open(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        // Safety: the index is only ever replaced by rename, never written in place.
+        let map = unsafe { Mmap::map(&file) }.ok()?;
+
+        if map.len() < HEADER_LEN || &map[0..8] != MAGIC {
+            eprintln!("Ignoring {}: not an index file", path.display());
+            return None;
+        }
+        let header = |n: usize| u64::from_le_bytes(map[8 + n * 8..16 + n * 8].try_into().unwrap());
+        if header(0) != VERSION {
+            eprintln!(
+                "Ignoring {}: index version {} but expected {VERSION}, re-run export-index",
+                path.display(),
+                header(0)
+            );
+            return None;
+        }
+
+        let stamp = header(1) as i64;
+        let counts = Counts {
+            shows: header(2) as usize,
+            peeps: header(3) as usize,
+            entries: header(4) as usize,
+            job_codes: header(5) as usize,
+            episodes: header(6) as usize,
+            peep_shows: header(7) as usize,
+            jobs: header(8) as usize,
+            strings: header(9) as usize,
+        };
+        if map.len() != counts.file_len() {
+            eprintln!("Ignoring {}: file is truncated", path.display());
+            return None;
+        }
+
+        Some(Self {
+            sections: counts.sections(),
+            map,
+            stamp,
+            counts,
+        })
+    }
+
+    /// Opens the index only if it was exported from this exact database build.
+    pub fn open_matching(db: &Connection) -> Option<Self> {
+        let index = Self::open(Path::new(INDEX_PATH))?;
+        if db::build_stamp(db) != Some(index.stamp) {
+            eprintln!(
+                "Ignoring {INDEX_PATH}: built from a different database, re-run export-index"
+            );
+            return None;
+        }
+        Some(index)
+    }
+
+    fn u16_at(&self, at: usize) -> u16 {
+        u16::from_le_bytes(self.map[at..at + 2].try_into().unwrap())
+    }
+
+    fn u32_at(&self, at: usize) -> u32 {
+        u32::from_le_bytes(self.map[at..at + 4].try_into().unwrap())
+    }
+
+    fn i64_at(&self, at: usize) -> i64 {
+        i64::from_le_bytes(self.map[at..at + 8].try_into().unwrap())
+    }
+
+    fn str_at(&self, at: usize) -> Option<&str> {
+        let off = self.u32_at(at);
+        if off == NO_STR {
+            return None;
+        }
+        let start = self.sections.strings + off as usize;
+        let len = self.u32_at(at + 4) as usize;
+        Some(std::str::from_utf8(&self.map[start..start + len]).expect("index strings are utf8"))
+    }
+
+    fn range_at(&self, at: usize) -> std::ops::Range<usize> {
+        let start = self.u32_at(at) as usize;
+        start..start + self.u32_at(at + 4) as usize
+    }
+
+    fn show_row(&self, i: usize) -> usize {
+        self.sections.shows + i * SHOW_ROW
+    }
+
+    fn peep_row(&self, i: usize) -> usize {
+        self.sections.peeps + i * PEEP_ROW
+    }
+
+    fn find(&self, id: i64, len: usize, row: impl Fn(usize) -> usize) -> Option<usize> {
+        let (mut lo, mut hi) = (0, len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.i64_at(row(mid)).cmp(&id) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    pub fn show(&self, id: i64) -> Option<IndexedShow<'_>> {
+        let row = self.show_row(self.find(id, self.counts.shows, |i| self.show_row(i))?);
+
+        let episodes = self
+            .range_at(row + 56)
+            .map(|e| self.i64_at(self.sections.episodes + e * 8))
+            .collect();
+
+        let credits = self
+            .range_at(row + 48)
+            .map(|e| {
+                let entry = self.sections.entries + e * ENTRY_ROW;
+                let peep = self.peep_row(self.u32_at(entry) as usize);
+                let jobs_start = self.u32_at(entry + 8) as usize;
+                let jobs_len = self.u16_at(entry + 12) as usize;
+                IndexedCredit {
+                    peep_id: self.i64_at(peep),
+                    name: self.str_at(peep + 8).unwrap_or_default(),
+                    jobs: (jobs_start..jobs_start + jobs_len)
+                        .map(|j| {
+                            let code = self.u32_at(self.sections.job_codes + j * 4) as usize;
+                            self.str_at(self.sections.jobs + code * STR_REF)
+                                .unwrap_or_default()
+                        })
+                        .collect(),
+                    episode_count: self.u32_at(entry + 4) as usize,
+                    root_credit: self.u16_at(entry + 14) != 0,
+                }
+            })
+            .collect();
+
+        Some(IndexedShow {
+            id,
+            title: self.str_at(row + 8).unwrap_or_default(),
+            title_type: self.str_at(row + 16).unwrap_or_default(),
+            start_year: self.str_at(row + 24).unwrap_or_default(),
+            genres: self.str_at(row + 32).unwrap_or_default(),
+            rating: self.str_at(row + 40),
+            votes: self.votes_at(row),
+            episodes,
+            credits,
+        })
+    }
+
+    fn votes_at(&self, row: usize) -> Option<i64> {
+        Some(self.i64_at(row + 64)).filter(|v| *v != NO_VOTES)
+    }
+
+    /// Just the fields a `ShowFilter` looks at, without reading any credits.
+    pub fn show_meta(&self, id: i64) -> Option<IndexedMeta<'_>> {
+        let row = self.show_row(self.find(id, self.counts.shows, |i| self.show_row(i))?);
+        Some(IndexedMeta {
+            title_type: self.str_at(row + 16).unwrap_or_default(),
+            start_year: self.str_at(row + 24).unwrap_or_default(),
+            genres: self.str_at(row + 32).unwrap_or_default(),
+            rating: self.str_at(row + 40),
+            votes: self.votes_at(row),
+        })
+    }
+
+    /// Every series the person is credited on, directly or through an episode.
+    pub fn peep_show_ids(&self, peep_id: i64) -> Vec<i64> {
+        let Some(peep) = self.find(peep_id, self.counts.peeps, |i| self.peep_row(i)) else {
+            return vec![];
+        };
+        self.range_at(self.peep_row(peep) + 16)
+            .map(|s| {
+                let show = self.u32_at(self.sections.peep_shows + s * 4) as usize;
+                self.i64_at(self.show_row(show))
+            })
+            .collect()
+    }
+}
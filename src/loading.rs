@@ -20,12 +20,15 @@ fn cell_id(cells: &mut Split<char>, nth: usize, prefix: &str) -> u64 {
         .expect("valid ID")
 }
 
+fn cell_num(cells: &mut Split<char>, nth: usize) -> u64 {
+    cells.nth(nth).unwrap().parse().expect("valid number")
+}
+
 #[derive(Debug)]
 pub struct Show {
     pub id: u64,
     pub title_type: String,
     pub title: String,
-    pub original_title: String,
     pub start_year: String,
     pub genres: String,
 }
@@ -42,14 +45,13 @@ impl DataType for Show {
             id: cell_id(&mut cells, 0, "tt"),
             title_type: cell_str(&mut cells, 0),
             title: cell_str(&mut cells, 0),
-            original_title: cell_str(&mut cells, 0),
-            start_year: cell_str(&mut cells, 1),
+            start_year: cell_str(&mut cells, 2),
             genres: cell_str(&mut cells, 2),
         }
     }
 
     fn id(&self) -> u64 {
-        self.id.clone()
+        self.id
     }
 }
 
@@ -73,7 +75,7 @@ impl DataType for Episode {
     }
 
     fn id(&self) -> u64 {
-        self.id.clone()
+        self.id
     }
 }
 
@@ -101,7 +103,7 @@ impl DataType for Principal {
     }
 
     fn id(&self) -> u64 {
-        self.peep_id.clone()
+        self.peep_id
     }
 }
 
@@ -109,6 +111,7 @@ impl DataType for Principal {
 pub struct Rating {
     pub show_id: u64,
     pub rating: String,
+    pub votes: u64,
 }
 
 impl DataType for Rating {
@@ -121,11 +124,12 @@ impl DataType for Rating {
         Self {
             show_id: cell_id(&mut cells, 0, "tt"),
             rating: cell_str(&mut cells, 0),
+            votes: cell_num(&mut cells, 0),
         }
     }
 
     fn id(&self) -> u64 {
-        self.show_id.clone()
+        self.show_id
     }
 }
 
@@ -151,6 +155,6 @@ impl DataType for Peep {
     }
 
     fn id(&self) -> u64 {
-        self.id.clone()
+        self.id
     }
 }
@@ -1,9 +1,30 @@
-use search::search;
+use std::{path::PathBuf, process};
+
+use search::{
+    head_to_head, init_taxonomy, path, recommend, search, why_not, RecencyMode, SearchOptions,
+};
 
 mod db;
+mod index;
 mod loading;
 mod search;
 
+fn list(value: std::ffi::OsString) -> Vec<String> {
+    value
+        .to_string_lossy()
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Stops on bad input with just the message, the way an unexpected argument
+/// is reported, rather than a panic.
+fn exit_with(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
+
 fn main() {
     use lexopt::prelude::*;
 
@@ -12,20 +33,179 @@ fn main() {
     match parser.next().unwrap() {
         Some(Value(v)) if v == "build" => {
             db::create();
-            return;
         }
-        Some(Value(v)) if v == "search" => {
+        Some(Value(v)) if v == "export-index" => {
+            let db = rusqlite::Connection::open("caterer.db").expect("can open db");
+            if db::build_stamp(&db).is_none() {
+                exit_with(
+                    "caterer.db has no build stamp, it's too old to index, rebuild it with build",
+                );
+            }
+            db::upgrade(&db);
+            index::export(&db);
+        }
+        Some(Value(command))
+            if command == "search"
+                || command == "why-not"
+                || command == "recommend"
+                || command == "compare"
+                || command == "path" =>
+        {
+            // Each command takes only the flags it uses: compare just scores
+            // two shows, and path only follows people.
+            let searching = command == "search" || command == "why-not" || command == "recommend";
+            let scoring = searching || command == "compare";
             let mut titles = vec![];
-            while let Ok(Some(Value(arg))) = parser.next() {
-                titles.push(arg.to_string_lossy().into_owned());
+            let mut candidate = None;
+            let mut ratings = None;
+            let mut max_hops = 4;
+            let mut weights: Option<PathBuf> = None;
+            let mut options = SearchOptions::default();
+            while let Some(arg) = parser.next().unwrap() {
+                match arg {
+                    Value(arg) => titles.push(arg.to_string_lossy().into_owned()),
+                    Long("color") => options.color = parser.value().unwrap().parse().unwrap(),
+                    Long("weights") => weights = Some(parser.value().unwrap().into()),
+                    Long("roles") => options.roles = list(parser.value().unwrap()),
+                    Long("exclude-roles") => options.exclude_roles = list(parser.value().unwrap()),
+                    Long("limit") if searching || command == "path" => {
+                        options.limit = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("scorer") if scoring => {
+                        options.scorer = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("explain") if scoring => options.explain = true,
+                    Long("genre-weight") if scoring => {
+                        options.genre_weight = parser.value().unwrap().parse().unwrap();
+                        if !(0.0..=1.0).contains(&options.genre_weight) {
+                            exit_with("--genre-weight must be between 0 and 1");
+                        }
+                    }
+                    Long("decay") if scoring => {
+                        options.recency.half_life = parser.value().unwrap().parse().unwrap();
+                        let half_life = options.recency.half_life;
+                        if !(half_life > 0.0 && half_life.is_finite()) {
+                            exit_with("--decay must be a number of years above 0");
+                        }
+                        if options.recency.mode == RecencyMode::Off {
+                            options.recency.mode = RecencyMode::Gap;
+                        }
+                    }
+                    Long("prefer-recent") if scoring => {
+                        options.recency.mode = RecencyMode::PreferRecent;
+                    }
+                    Long("era") if scoring => {
+                        options.recency.mode =
+                            RecencyMode::Era(parser.value().unwrap().parse().unwrap());
+                    }
+                    Long("max-candidates") if searching => {
+                        options.max_candidates = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("offset") if searching => {
+                        options.offset = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("format") if searching => {
+                        options.format = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("output") | Short('o') if searching => {
+                        options.output = Some(parser.value().unwrap().into());
+                    }
+                    Long("person") if searching => options
+                        .people
+                        .push(parser.value().unwrap().string().unwrap()),
+                    Long("not") if searching => options
+                        .not_like
+                        .push(parser.value().unwrap().string().unwrap()),
+                    Long("type") if searching => {
+                        options.filter.types = list(parser.value().unwrap())
+                    }
+                    Long("years") if searching => {
+                        options.filter.years = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("genre") if searching => {
+                        options.filter.genres = list(parser.value().unwrap())
+                    }
+                    Long("exclude-genre") if searching => {
+                        options.filter.exclude_genres = list(parser.value().unwrap());
+                    }
+                    Long("min-rating") if searching => {
+                        options.filter.min_rating = Some(parser.value().unwrap().parse().unwrap());
+                    }
+                    Long("min-votes") if searching => {
+                        options.filter.min_votes = Some(parser.value().unwrap().parse().unwrap());
+                    }
+                    Long("seen") if searching => options.seen.push(parser.value().unwrap().into()),
+                    Long("seen-weight") if searching => {
+                        options.seen_weight = parser.value().unwrap().parse().unwrap();
+                        if !(0.0..=1.0).contains(&options.seen_weight) {
+                            exit_with("--seen-weight must be between 0 and 1");
+                        }
+                    }
+                    Long("diversity") if searching => {
+                        options.diversity = parser.value().unwrap().parse().unwrap();
+                        if !(0.0..=1.0).contains(&options.diversity) {
+                            exit_with("--diversity must be between 0 and 1");
+                        }
+                    }
+                    Long("group-by") if searching => {
+                        options.group_by = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("hide-franchise") if searching => {
+                        options.franchise = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("min-score") if searching => {
+                        options.min_score = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("candidate") if command == "why-not" => {
+                        candidate = Some(parser.value().unwrap().string().unwrap());
+                    }
+                    Long("max-hops") if command == "path" => {
+                        max_hops = parser.value().unwrap().parse().unwrap();
+                    }
+                    Long("ratings") if command == "recommend" => {
+                        ratings = Some(parser.value().unwrap().into());
+                    }
+                    arg => {
+                        eprintln!("{}", arg.unexpected());
+                        return;
+                    }
+                }
+            }
+
+            init_taxonomy(weights.as_deref());
+
+            if command == "compare" {
+                head_to_head(titles, options);
+                return;
+            }
+            if command == "path" {
+                let [from, to] =
+                    <[String; 2]>::try_from(titles).expect("path needs exactly a from and a to");
+                path(from, to, max_hops, options);
+                return;
             }
 
-            println!("Starting search with: {}", titles.join(", "));
+            let seeds: Vec<&str> = titles
+                .iter()
+                .chain(&options.people)
+                .map(String::as_str)
+                .collect();
+            eprintln!("Starting search with: {}", seeds.join(", "));
 
-            search(titles);
+            if command == "why-not" {
+                let candidate = candidate.expect("why-not needs a --candidate");
+                why_not(titles, candidate, options);
+            } else if command == "recommend" {
+                let ratings = ratings.expect("recommend needs --ratings");
+                recommend(titles, ratings, options);
+            } else {
+                search(titles, options);
+            }
         }
         _ => {
-            eprintln!("Must supply either build or search command");
+            eprintln!(
+                "Must supply either build, export-index, search, why-not, recommend, compare or path command"
+            );
         }
     }
 }
@@ -1,27 +1,55 @@
+use std::{collections::HashSet, slice};
+
 use console::{style, Color};
+use serde::Serialize;
 
-use super::show_tree::ShowRecord;
+use super::{
+    recency::Recency,
+    scorer::{ExplainTerm, Explanation, Scorer},
+    show_tree::ShowRecord,
+    taxonomy::taxonomy,
+};
 
 pub struct ShowAffinity {
     pub show: ShowRecord,
     pub score: f32,
+    /// The score before any `--not` penalties, for why-not to say what they
+    /// took off.
+    pub base_score: f32,
     pub credits: Vec<AffinityCredit>,
+    /// Only kept when asked for with `--explain`.
+    pub explanation: Option<Explanation>,
+    /// Results from the same franchise folded into this one by
+    /// `--hide-franchise group`.
+    pub franchise: Vec<ShowRecord>,
 }
 
+/// One person shared between a root show and the candidate. Episode counts
+/// are 0 when the credit is only on the series itself.
+#[derive(Serialize)]
 pub struct AffinityCredit {
-    pub text: String,
-    pub bar: String,
+    pub peep_id: i64,
     pub name: String,
+    pub root_show_id: i64,
+    pub root_show_title: String,
+    pub root_jobs: Vec<String>,
+    pub root_episodes: usize,
+    pub root_episode_total: usize,
+    pub root_direct: bool,
+    pub candidate_jobs: Vec<String>,
+    pub candidate_episodes: usize,
+    pub candidate_episode_total: usize,
+    pub candidate_direct: bool,
 }
 
-fn calc_overlap_bar(
-    l_color: Color,
-    r_color: Color,
+/// How many of the 10 cells on each side of an overlap bar are filled: the
+/// person's share of episodes, or a full side for series-level credits.
+pub fn overlap_cells(
     mut l_eps: usize,
     mut l_peep_eps: usize,
     mut r_eps: usize,
     mut r_peep_eps: usize,
-) -> String {
+) -> (usize, usize) {
     if l_eps == 0 {
         l_eps = 10;
     }
@@ -39,119 +67,173 @@ fn calc_overlap_bar(
     let l_peep_chars = (((l_peep_eps as f32 / l_eps as f32) * 10.0).ceil() as usize).min(10);
     let r_peep_chars = (((r_peep_eps as f32 / r_eps as f32) * 10.0).ceil() as usize).min(10);
 
+    (l_peep_chars, r_peep_chars)
+}
+
+/// With `ascii`, bars are drawn with `#` and `-` rather than box-drawing
+/// characters.
+pub fn calc_overlap_bar(
+    ascii: bool,
+    l_color: Color,
+    r_color: Color,
+    l_eps: usize,
+    l_peep_eps: usize,
+    r_eps: usize,
+    r_peep_eps: usize,
+) -> String {
+    let (l_peep_chars, r_peep_chars) = overlap_cells(l_eps, l_peep_eps, r_eps, r_peep_eps);
+
+    let (empty, full) = if ascii { ("-", "#") } else { ("─", "▓") };
+
     format!(
         "{}{} / {}{}",
-        style(vec!["─"; 10 - l_peep_chars].join("")).red().dim(),
-        style(vec!["▓"; l_peep_chars].join("")).fg(l_color),
-        style(vec!["▓"; r_peep_chars].join("")).fg(r_color),
-        style(vec!["─"; 10 - r_peep_chars].join("")).red().dim(),
+        style(empty.repeat(10 - l_peep_chars)).red().dim(),
+        style(full.repeat(l_peep_chars)).fg(l_color),
+        style(full.repeat(r_peep_chars)).fg(r_color),
+        style(empty.repeat(10 - r_peep_chars)).red().dim(),
     )
 }
 
-pub fn score_show_affinity(root_shows: &[ShowRecord], candidate_show: ShowRecord) -> ShowAffinity {
-    let mut score = 0.0;
+fn genre_set(show: &ShowRecord) -> HashSet<&str> {
+    show.genres
+        .split(',')
+        .filter(|g| !g.is_empty() && *g != "\\N")
+        .collect()
+}
+
+/// Jaccard overlap of the candidate's genres with each root show's, averaged
+/// by root weight. Root shows without genres are left out, and `None` when
+/// that's all of them. A candidate without genres overlaps 0, so it can't
+/// outrank one whose genres are known not to match.
+fn genre_overlap(
+    root_shows: &[ShowRecord],
+    root_weights: &[f32],
+    candidate_show: &ShowRecord,
+) -> Option<f32> {
+    let candidate_genres = genre_set(candidate_show);
+
+    let mut overlap = 0.0;
+    let mut total_weight = 0.0;
+    for (root_show, &weight) in root_shows.iter().zip(root_weights) {
+        let root_genres = genre_set(root_show);
+        if root_genres.is_empty() {
+            continue;
+        }
+        let shared = root_genres.intersection(&candidate_genres).count();
+        let union = root_genres.union(&candidate_genres).count();
+        overlap += weight * shared as f32 / union as f32;
+        total_weight += weight;
+    }
+    (total_weight > 0.0).then(|| overlap / total_weight)
+}
+
+/// Blends genre overlap into a crew-overlap score: with `genre_weight` at 0
+/// genres are ignored, at 1 a show sharing no genres scores nothing.
+fn blend_genres(
+    explanation: &mut Explanation,
+    root_shows: &[ShowRecord],
+    root_weights: &[f32],
+    candidate_show: &ShowRecord,
+    genre_weight: f32,
+) {
+    if genre_weight <= 0.0 {
+        return;
+    }
+    let Some(overlap) = genre_overlap(root_shows, root_weights, candidate_show) else {
+        return;
+    };
+    let value = 1.0 - genre_weight + genre_weight * overlap;
+    explanation.multipliers.push(ExplainTerm {
+        label: format!("genres overlap {overlap:.2}, blended at {genre_weight}"),
+        value,
+    });
+    explanation.score *= value;
+}
+
+/// Credits and scores a candidate against the root shows. Each `--not` show
+/// is scored against the candidate with the same scorer on its own, and that
+/// score is taken off. Root shows count for their seed weight times any
+/// recency decay, and every score is blended with its genre overlap.
+pub fn score_show_affinity(
+    root_shows: &[ShowRecord],
+    negative_shows: &[ShowRecord],
+    candidate_show: ShowRecord,
+    scorer: &dyn Scorer,
+    recency: &Recency,
+    genre_weight: f32,
+    explain: bool,
+) -> ShowAffinity {
+    let root_weight =
+        |root_show: &ShowRecord| root_show.weight * recency.factor(root_show, &candidate_show);
+
     let mut credits: Vec<AffinityCredit> = vec![];
-    let mut root_show_count = 0;
     for root_show in root_shows {
-        let mut has_stylistic_peep_overlap = false;
-
         for root_peep in root_show.peeps.values() {
             if let Some(candidate_peep) = candidate_show.peeps.get(&root_peep.id) {
-                let existing_credit_count =
-                    credits.iter().filter(|c| c.name == root_peep.name).count();
-                if existing_credit_count > 0 {
-                    score += (root_peep.score * candidate_peep.score)
-                        / (existing_credit_count + 2) as f32;
-                } else {
-                    score += root_peep.score * candidate_peep.score;
-                }
-                if candidate_peep.stylistic && existing_credit_count == 0 {
-                    has_stylistic_peep_overlap = true;
+                if !root_peep.focused || !candidate_peep.focused {
+                    continue;
                 }
-                let name = &root_peep.name;
-                let root_jobs = style(
-                    root_peep
-                        .jobs
-                        .iter()
-                        .map(|j| style(j).fg(job_color(j)).to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                )
-                .cyan();
-                let candidate_jobs = style(
-                    candidate_peep
-                        .jobs
-                        .iter()
-                        .map(|j| style(j).fg(job_color(j)).to_string())
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                )
-                .cyan();
-
-                let title = style(&root_show.title).bold().underlined();
-
-                let root_peep_eps = root_peep.episode_count;
-                let root_eps = root_show.episodes.len();
-                let root_cred = if root_peep_eps > 0 {
-                    format!("[{title}] {name}: {root_peep_eps}/{root_eps} ({root_jobs})")
-                } else {
-                    format!("[{title}] {name}: ({root_jobs})")
-                };
-
-                let candidate_peep_eps = candidate_peep.episode_count;
-                let candidate_eps = candidate_show.episodes.len();
-                let candidate_cred = if candidate_peep_eps > 0 {
-                    format!("{candidate_peep_eps}/{candidate_eps} ({candidate_jobs})")
-                } else {
-                    format!("({candidate_jobs})")
-                };
-
-                let l_color = job_color(best_job(&root_peep.jobs));
-                let r_color = job_color(best_job(&candidate_peep.jobs));
-
-                let bar = calc_overlap_bar(
-                    l_color,
-                    r_color,
-                    root_eps,
-                    root_peep_eps,
-                    candidate_eps,
-                    candidate_peep_eps,
-                );
-
                 credits.push(AffinityCredit {
-                    text: format!("{root_cred} → {candidate_cred}"),
-                    bar: format!("{bar}"),
+                    peep_id: root_peep.id,
                     name: root_peep.name.clone(),
+                    root_show_id: root_show.id,
+                    root_show_title: root_show.title.clone(),
+                    root_jobs: root_peep.jobs.clone(),
+                    root_episodes: root_peep.episode_count,
+                    root_episode_total: root_show.episodes.len(),
+                    root_direct: root_peep.root_credit,
+                    candidate_jobs: candidate_peep.jobs.clone(),
+                    candidate_episodes: candidate_peep.episode_count,
+                    candidate_episode_total: candidate_show.episodes.len(),
+                    candidate_direct: candidate_peep.root_credit,
                 });
             }
         }
-
-        if has_stylistic_peep_overlap {
-            root_show_count += 1;
-        }
     }
 
-    let parsed_rating: f32 = candidate_show
-        .rating
-        .as_ref()
-        .map(|r| r.parse().unwrap_or(5.0))
-        .unwrap_or(5.0);
-
-    score *= parsed_rating.powf(2.0);
-    if root_show_count > 1 {
-        score *= (root_show_count as f32) / 2.0;
+    let root_weights: Vec<f32> = root_shows.iter().map(root_weight).collect();
+    let mut explanation = scorer.explain(root_shows, &root_weights, &candidate_show);
+    blend_genres(
+        &mut explanation,
+        root_shows,
+        &root_weights,
+        &candidate_show,
+        genre_weight,
+    );
+    let base_score = explanation.score;
+    for negative_show in negative_shows {
+        let negative_shows = slice::from_ref(negative_show);
+        let negative_weights = [root_weight(negative_show)];
+        let mut penalty = scorer.explain(negative_shows, &negative_weights, &candidate_show);
+        blend_genres(
+            &mut penalty,
+            negative_shows,
+            &negative_weights,
+            &candidate_show,
+            genre_weight,
+        );
+        if penalty.score > 0.0 {
+            explanation.penalties.push(ExplainTerm {
+                label: format!("not like [{}]", negative_show.title),
+                value: penalty.score,
+            });
+            explanation.score -= penalty.score;
+        }
     }
 
     credits.sort_by(|a, b| a.name.cmp(&b.name));
 
     ShowAffinity {
         show: candidate_show,
-        score,
+        score: explanation.score,
+        base_score,
         credits,
+        explanation: explain.then_some(explanation),
+        franchise: vec![],
     }
 }
 
-pub fn best_job(roles: &Vec<String>) -> &String {
+pub fn best_job(roles: &[String]) -> &String {
     roles
         .iter()
         .reduce(|r1, r2| if job_buff(r1) > job_buff(r2) { r1 } else { r2 })
@@ -159,71 +241,13 @@ pub fn best_job(roles: &Vec<String>) -> &String {
 }
 
 pub fn normalize_job(role: &str) -> &str {
-    let r = role.to_lowercase();
-    if r.contains("written")
-        || r.contains("script")
-        || r.contains("writer")
-        || r.contains("developed")
-        || r.contains("created")
-        || r.contains("creator")
-        || r.contains("story")
-        || r.contains("screenplay")
-        || r.contains("writing")
-        || r.contains("adapted")
-        || r.contains("devise")
-        || r.contains("book")
-        || r.contains("play")
-        || r.contains("idea")
-    {
-        "written by"
-    } else if r.contains("casting") {
-        "casting_director"
-    } else if r.contains("designer") {
-        "production_designer"
-    } else if r.contains("editor") {
-        "editor"
-    } else if r.contains("composer") {
-        "composer"
-    } else if r.contains("cinematographer") || r.contains("photograph") {
-        "cinematographer"
-    } else if r.contains("producer") {
-        "producer"
-    } else if r.contains("based") || r.contains("original") || r.contains("novel") {
-        "based on"
-    } else if r.contains("director") || r.contains("showrunner") || r.contains("directed") {
-        "director"
-    } else {
-        role
-    }
+    taxonomy().normalize(role).unwrap_or(role)
 }
 
 pub fn job_buff(role: &str) -> f32 {
-    (match normalize_job(role) {
-        "cinematographer" | "director of photography" => 60,
-        "director" => 50,
-        "writer" | "written by" | "original idea" | "creator" => 40,
-        "composer" => 40,
-        "production_designer" => 30,
-        "editor" => 20,
-        "based on" => 20,
-        "producer" => 20,
-        "casting_director" => 10,
-        _ => 1,
-    }) as f32
+    taxonomy().weight(normalize_job(role))
 }
 
 pub fn job_color(role: &str) -> console::Color {
-    use console::Color::*;
-    match normalize_job(role) {
-        "cinematographer" | "director of photography" => Magenta,
-        "composer" => Green,
-        "director" => Cyan,
-        "writer" | "written by" | "original idea" | "creator" => Yellow,
-        "based on" => Yellow,
-        "production_designer" => Blue,
-        "editor" => Blue,
-        "producer" => Blue,
-        "casting_director" => Blue,
-        _ => Red,
-    }
+    taxonomy().color(normalize_job(role))
 }
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use super::compare::ShowAffinity;
+
+fn jaccard(a: &HashSet<i64>, b: &HashSet<i64>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(b).count() as f32 / union as f32
+    }
+}
+
+/// Maximal marginal relevance: greedily picks the next result by its score
+/// (relative to the best) minus how much its credited people overlap the
+/// most similar result already picked, with `diversity` weighing the second
+/// against the first. Only the first `picks` places are re-ranked, the rest
+/// keep their order after them. Scores are left as they were.
+pub fn diversify(affinities: Vec<ShowAffinity>, diversity: f32, picks: usize) -> Vec<ShowAffinity> {
+    let top = affinities.first().map(|a| a.score).unwrap_or_default();
+    if top <= 0.0 {
+        return affinities;
+    }
+
+    let people: Vec<HashSet<i64>> = affinities
+        .iter()
+        .map(|a| a.credits.iter().map(|c| c.peep_id).collect())
+        .collect();
+    let n = affinities.len();
+    let mut max_similarity = vec![0.0f32; n];
+    let mut picked = vec![false; n];
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+
+    for _ in 0..picks.min(n) {
+        let mut best: Option<(usize, f32)> = None;
+        for i in (0..n).filter(|i| !picked[*i]) {
+            let mmr = (1.0 - diversity) * affinities[i].score / top - diversity * max_similarity[i];
+            if best.is_none_or(|(_, b)| mmr > b) {
+                best = Some((i, mmr));
+            }
+        }
+        let Some((next, _)) = best else {
+            break;
+        };
+
+        picked[next] = true;
+        order.push(next);
+        for i in (0..n).filter(|i| !picked[*i]) {
+            max_similarity[i] = max_similarity[i].max(jaccard(&people[next], &people[i]));
+        }
+    }
+    order.extend((0..n).filter(|i| !picked[*i]));
+
+    let mut slots: Vec<Option<ShowAffinity>> = affinities.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .map(|i| slots[i].take().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diversify;
+    use crate::search::{
+        compare::{AffinityCredit, ShowAffinity},
+        show_tree::ShowRecord,
+    };
+
+    fn affinity(id: i64, score: f32, people: &[i64]) -> ShowAffinity {
+        ShowAffinity {
+            show: ShowRecord::for_test(id, &format!("Show {id}"), None, &[]),
+            score,
+            base_score: score,
+            credits: people
+                .iter()
+                .map(|&peep_id| AffinityCredit {
+                    peep_id,
+                    name: format!("Person {peep_id}"),
+                    root_show_id: 0,
+                    root_show_title: String::new(),
+                    root_jobs: vec![],
+                    root_episodes: 0,
+                    root_episode_total: 0,
+                    root_direct: true,
+                    candidate_jobs: vec![],
+                    candidate_episodes: 0,
+                    candidate_episode_total: 0,
+                    candidate_direct: true,
+                })
+                .collect(),
+            explanation: None,
+            franchise: vec![],
+        }
+    }
+
+    fn ids(affinities: &[ShowAffinity]) -> Vec<i64> {
+        affinities.iter().map(|a| a.show.id).collect()
+    }
+
+    fn results() -> Vec<ShowAffinity> {
+        vec![
+            affinity(1, 10.0, &[1, 2]),
+            affinity(2, 9.0, &[1, 2]),
+            affinity(3, 8.0, &[3]),
+        ]
+    }
+
+    #[test]
+    fn no_diversity_keeps_the_ranking() {
+        assert_eq!(ids(&diversify(results(), 0.0, 3)), [1, 2, 3]);
+    }
+
+    #[test]
+    fn results_sharing_people_are_pushed_down() {
+        let diversified = diversify(results(), 0.5, 3);
+        assert_eq!(ids(&diversified), [1, 3, 2]);
+        assert_eq!(diversified[1].score, 8.0);
+    }
+
+    #[test]
+    fn only_the_picks_are_reranked() {
+        assert_eq!(ids(&diversify(results(), 0.5, 1)), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{fmt, str::FromStr};
+
+use super::show_tree::ShowRecord;
+
+/// The fields of a show a `ShowFilter` looks at, cheap enough to read for
+/// every linked show before any of them are hydrated.
+pub struct ShowMeta {
+    pub id: i64,
+    pub title_type: String,
+    pub start_year: String,
+    pub genres: String,
+    pub rating: Option<String>,
+    pub votes: Option<i64>,
+}
+
+impl From<&ShowRecord> for ShowMeta {
+    fn from(show: &ShowRecord) -> Self {
+        Self {
+            id: show.id,
+            title_type: show.title_type.clone(),
+            start_year: show.start_year.clone(),
+            genres: show.genres.clone(),
+            rating: show.rating.clone(),
+            votes: show.votes,
+        }
+    }
+}
+
+/// `2010..`, `..2015`, `2010..2015` or just `2012`, both ends inclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct YearRange {
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+}
+
+impl FromStr for YearRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let year = |y: &str| -> Result<Option<u32>, String> {
+            if y.is_empty() {
+                return Ok(None);
+            }
+            y.parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid year {y} in {s}, expected e.g. 2010..2015"))
+        };
+        match s.split_once("..") {
+            Some((from, to)) => Ok(Self {
+                from: year(from)?,
+                to: year(to)?,
+            }),
+            None => {
+                let y = year(s)?;
+                Ok(Self { from: y, to: y })
+            }
+        }
+    }
+}
+
+impl YearRange {
+    fn is_active(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    fn contains(&self, year: u32) -> bool {
+        self.from.is_none_or(|f| year >= f) && self.to.is_none_or(|t| year <= t)
+    }
+}
+
+impl fmt::Display for YearRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from == to => write!(f, "{from}"),
+            (from, to) => write!(
+                f,
+                "{}..{}",
+                from.map(|y| y.to_string()).unwrap_or_default(),
+                to.map(|y| y.to_string()).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// Restricts which linked shows are kept, checked before any are hydrated.
+/// Types and genres match case-insensitively; a show needs any one of the
+/// `genres` and none of the `exclude_genres`. Shows with no year, rating or
+/// vote count fail a filter on that field.
+#[derive(Default)]
+pub struct ShowFilter {
+    pub types: Vec<String>,
+    pub years: YearRange,
+    pub genres: Vec<String>,
+    pub exclude_genres: Vec<String>,
+    pub min_rating: Option<f32>,
+    pub min_votes: Option<i64>,
+}
+
+impl ShowFilter {
+    pub fn is_active(&self) -> bool {
+        !self.types.is_empty()
+            || self.years.is_active()
+            || !self.genres.is_empty()
+            || !self.exclude_genres.is_empty()
+            || self.min_rating.is_some()
+            || self.min_votes.is_some()
+    }
+
+    pub fn allows(&self, show: &ShowMeta) -> bool {
+        let has_genre = |wanted: &[String]| {
+            show.genres
+                .split(',')
+                .any(|g| wanted.iter().any(|w| w.eq_ignore_ascii_case(g)))
+        };
+
+        (self.types.is_empty()
+            || self
+                .types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&show.title_type)))
+            && (!self.years.is_active()
+                || show
+                    .start_year
+                    .parse()
+                    .is_ok_and(|y| self.years.contains(y)))
+            && (self.genres.is_empty() || has_genre(&self.genres))
+            && !has_genre(&self.exclude_genres)
+            && self.min_rating.is_none_or(|min| {
+                show.rating
+                    .as_deref()
+                    .and_then(|r| r.parse::<f32>().ok())
+                    .is_some_and(|r| r >= min)
+            })
+            && self
+                .min_votes
+                .is_none_or(|min| show.votes.is_some_and(|v| v >= min))
+    }
+}
+
+impl fmt::Display for ShowFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = vec![];
+        if !self.types.is_empty() {
+            parts.push(self.types.join(" or "));
+        }
+        if self.years.is_active() {
+            parts.push(format!("from {}", self.years));
+        }
+        if !self.genres.is_empty() {
+            parts.push(format!("in {}", self.genres.join(" or ")));
+        }
+        if !self.exclude_genres.is_empty() {
+            parts.push(format!("not in {}", self.exclude_genres.join(" or ")));
+        }
+        if let Some(min) = self.min_rating {
+            parts.push(format!("rated {min}+"));
+        }
+        if let Some(min) = self.min_votes {
+            parts.push(format!("with {min}+ votes"));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::YearRange;
+
+    fn range(s: &str) -> (Option<u32>, Option<u32>) {
+        let range: YearRange = s.parse().unwrap();
+        (range.from, range.to)
+    }
+
+    #[test]
+    fn year_ranges_parse() {
+        assert_eq!(range("2010..2015"), (Some(2010), Some(2015)));
+        assert_eq!(range("2010.."), (Some(2010), None));
+        assert_eq!(range("..2015"), (None, Some(2015)));
+        assert_eq!(range("2012"), (Some(2012), Some(2012)));
+        assert_eq!(range(""), (None, None));
+        assert!("20x0..2015".parse::<YearRange>().is_err());
+        assert!("2010-2015".parse::<YearRange>().is_err());
+    }
+
+    #[test]
+    fn year_ranges_display_as_parsed() {
+        for s in ["2010..2015", "2010..", "..2015", "2012"] {
+            assert_eq!(s.parse::<YearRange>().unwrap().to_string(), s);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashSet, str::FromStr};
+
+use super::{
+    compare::{normalize_job, ShowAffinity},
+    show_tree::ShowRecord,
+};
+
+/// A candidate sharing at least this share of a seed's creators is taken to
+/// be a spin-off or sequel of it.
+const SHARED_CREATORS: f32 = 0.5;
+
+/// What `--hide-franchise` does with results from the same franchise as a
+/// seed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FranchiseMode {
+    #[default]
+    Keep,
+    /// Only the best scoring one is kept, listing the others.
+    Group,
+    Remove,
+}
+
+impl FromStr for FranchiseMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(FranchiseMode::Keep),
+            "group" => Ok(FranchiseMode::Group),
+            "remove" => Ok(FranchiseMode::Remove),
+            _ => Err(format!(
+                "Unknown franchise mode {s}, expected keep, group or remove"
+            )),
+        }
+    }
+}
+
+/// People who wrote, created or supplied the source for a show. Only those
+/// credited on the series itself count when there are any, so a long list of
+/// episode writers doesn't water the ratio down.
+fn creators(show: &ShowRecord) -> HashSet<i64> {
+    let writers: Vec<_> = show
+        .peeps
+        .values()
+        .filter(|p| {
+            p.jobs
+                .iter()
+                .any(|j| matches!(normalize_job(j), "written by" | "based on"))
+        })
+        .collect();
+    let series_writers: HashSet<i64> = writers
+        .iter()
+        .filter(|p| p.root_credit)
+        .map(|p| p.id)
+        .collect();
+    if series_writers.is_empty() {
+        writers.iter().map(|p| p.id).collect()
+    } else {
+        series_writers
+    }
+}
+
+fn title_words(title: &str) -> Vec<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether one title appears whole, word for word, inside the other, like
+/// "Breaking Bad" in "El Camino: A Breaking Bad Movie".
+fn titles_overlap(a: &str, b: &str) -> bool {
+    let (a, b) = (title_words(a), title_words(b));
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    !short.is_empty() && long.windows(short.len()).any(|w| w == short)
+}
+
+/// The seed a candidate looks to share a franchise with: most of the seed's
+/// creators worked on it, or it shares a creator and one title contains the
+/// other.
+pub fn franchise_of<'a>(seeds: &'a [ShowRecord], candidate: &ShowRecord) -> Option<&'a ShowRecord> {
+    let candidate_creators = creators(candidate);
+    seeds.iter().find(|seed| {
+        let seed_creators = creators(seed);
+        let shared = seed_creators.intersection(&candidate_creators).count();
+        shared > 0
+            && (shared as f32 / seed_creators.len() as f32 >= SHARED_CREATORS
+                || titles_overlap(&seed.title, &candidate.title))
+    })
+}
+
+/// Groups or removes results from the same franchise as a seed, keeping the
+/// order of the rest. Grouping keeps the best scoring one in place with the
+/// others in its `franchise`. Returns how many results were folded away.
+pub fn hide_franchises(
+    affinities: Vec<ShowAffinity>,
+    seeds: &[ShowRecord],
+    mode: FranchiseMode,
+) -> (Vec<ShowAffinity>, usize) {
+    if mode == FranchiseMode::Keep {
+        return (affinities, 0);
+    }
+
+    let mut kept: Vec<ShowAffinity> = Vec::with_capacity(affinities.len());
+    // For each seed, where its franchise's result sits in `kept`.
+    let mut groups: Vec<(i64, usize)> = vec![];
+    let mut hidden = 0;
+    for affinity in affinities {
+        let Some(seed) = franchise_of(seeds, &affinity.show) else {
+            kept.push(affinity);
+            continue;
+        };
+        match groups.iter().find(|(id, _)| *id == seed.id) {
+            Some(&(_, at)) if mode == FranchiseMode::Group => {
+                kept[at].franchise.push(affinity.show);
+                hidden += 1;
+            }
+            None if mode == FranchiseMode::Group => {
+                groups.push((seed.id, kept.len()));
+                kept.push(affinity);
+            }
+            _ => hidden += 1,
+        }
+    }
+    (kept, hidden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{franchise_of, titles_overlap};
+    use crate::search::show_tree::ShowRecord;
+
+    #[test]
+    fn titles_overlap_word_for_word() {
+        assert!(titles_overlap(
+            "Breaking Bad",
+            "El Camino: A Breaking Bad Movie"
+        ));
+        assert!(titles_overlap("The Office", "the office"));
+        assert!(!titles_overlap("The Wire", "Wired"));
+        assert!(!titles_overlap("Breaking Bad", "Bad Breaking"));
+    }
+
+    #[test]
+    fn most_creators_shared_is_a_franchise() {
+        let seeds = [ShowRecord::for_test(
+            1,
+            "Seed",
+            None,
+            &[(1, "writer"), (2, "created by"), (3, "composer")],
+        )];
+        let spin_off = ShowRecord::for_test(2, "Other", None, &[(1, "writer"), (9, "editor")]);
+        let unrelated = ShowRecord::for_test(3, "Other", None, &[(3, "composer"), (9, "writer")]);
+
+        assert_eq!(franchise_of(&seeds, &spin_off).map(|s| s.id), Some(1));
+        assert!(franchise_of(&seeds, &unrelated).is_none());
+    }
+
+    #[test]
+    fn one_creator_shared_needs_the_title() {
+        let seeds = [ShowRecord::for_test(
+            1,
+            "Breaking Bad",
+            None,
+            &[(1, "writer"), (2, "writer"), (3, "writer")],
+        )];
+        let movie =
+            ShowRecord::for_test(2, "El Camino: A Breaking Bad Movie", None, &[(1, "writer")]);
+        let other = ShowRecord::for_test(3, "Better Call Saul", None, &[(1, "writer")]);
+
+        assert_eq!(franchise_of(&seeds, &movie).map(|s| s.id), Some(1));
+        assert!(franchise_of(&seeds, &other).is_none());
+    }
+}
@@ -0,0 +1,95 @@
+use std::{collections::HashMap, str::FromStr};
+
+use super::compare::{AffinityCredit, ShowAffinity};
+
+/// How results are laid out: a list of shows, or pivoted to list each seed
+/// person with the shows they also worked on.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GroupBy {
+    #[default]
+    Show,
+    Person,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "show" => Ok(GroupBy::Show),
+            "person" => Ok(GroupBy::Person),
+            _ => Err(format!("Unknown grouping {s}, expected show or person")),
+        }
+    }
+}
+
+/// A result one person shares with the seeds, at its rank among all results.
+pub struct PersonShow<'a> {
+    pub rank: usize,
+    pub affinity: &'a ShowAffinity,
+    /// The person's credit on it, against the first seed they're on.
+    pub credit: &'a AffinityCredit,
+}
+
+/// One stylistic person from the seeds and every result they also worked on,
+/// best first.
+pub struct PersonGroup<'a> {
+    pub peep_id: i64,
+    pub name: &'a str,
+    /// The seeds they're credited on, with their jobs there.
+    pub root_credits: Vec<(&'a str, &'a [String])>,
+    pub shows: Vec<PersonShow<'a>>,
+    /// The summed score of their shows, which the groups are ordered by.
+    pub score: f32,
+}
+
+/// Pivots ranked results into one group per stylistic seed person, most
+/// relevant first: by the summed score of the results they worked on, then
+/// how many there are, then name.
+pub fn group_by_person<'a>(
+    affinities: &'a [ShowAffinity],
+    staff: &HashMap<i64, f32>,
+) -> Vec<PersonGroup<'a>> {
+    let mut groups: HashMap<i64, PersonGroup<'a>> = HashMap::new();
+    for (i, affinity) in affinities.iter().enumerate() {
+        for credit in &affinity.credits {
+            if !staff.contains_key(&credit.peep_id) {
+                continue;
+            }
+            let group = groups.entry(credit.peep_id).or_insert_with(|| PersonGroup {
+                peep_id: credit.peep_id,
+                name: &credit.name,
+                root_credits: vec![],
+                shows: vec![],
+                score: 0.0,
+            });
+            if !group
+                .root_credits
+                .iter()
+                .any(|(title, _)| *title == credit.root_show_title)
+            {
+                group
+                    .root_credits
+                    .push((&credit.root_show_title, &credit.root_jobs));
+            }
+            if group.shows.last().is_none_or(|s| s.rank != i + 1) {
+                group.shows.push(PersonShow {
+                    rank: i + 1,
+                    affinity,
+                    credit,
+                });
+                group.score += affinity.score;
+            }
+        }
+    }
+
+    let mut groups: Vec<PersonGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.shows.len().cmp(&a.shows.len()))
+            .then(a.name.cmp(b.name))
+            .then(a.peep_id.cmp(&b.peep_id))
+    });
+    groups
+}
@@ -0,0 +1,148 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    path::Path,
+    slice,
+};
+
+use console::style;
+use rusqlite::Connection;
+
+use super::{
+    compare::{best_job, calc_overlap_bar, job_color, score_show_affinity, ShowAffinity},
+    make_scorer,
+    render::configure_styling,
+    seeds::resolve_show,
+    show_tree::{ShowPeepRecord, ShowRecord},
+    source::Source,
+    taxonomy::RoleFilter,
+    SearchOptions,
+};
+
+/// One side of a shared person: their episode share and jobs on that show.
+fn side(peep: &ShowPeepRecord, show: &ShowRecord) -> String {
+    let jobs = peep
+        .jobs
+        .iter()
+        .map(|j| style(j).fg(job_color(j)).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if peep.episode_count > 0 {
+        format!("{}/{} ({jobs})", peep.episode_count, show.episodes.len())
+    } else {
+        format!("({jobs})")
+    }
+}
+
+fn write_score(out: &mut impl Write, from: &ShowRecord, affinity: &ShowAffinity) {
+    writeln!(
+        out,
+        "\n{} → {}: score {:.2}",
+        style(&from.title).bold(),
+        style(&affinity.show.title).bold(),
+        affinity.score
+    )
+    .unwrap();
+    if let Some(explanation) = &affinity.explanation {
+        for term in &explanation.terms {
+            writeln!(out, "  + {:>12.2}  {}", term.value, term.label).unwrap();
+        }
+        for multiplier in &explanation.multipliers {
+            writeln!(out, "  × {:>12.2}  {}", multiplier.value, multiplier.label).unwrap();
+        }
+    }
+}
+
+/// Compares two shows directly, without a search: every person they share
+/// side by side, and how each scores with the other as the only seed.
+pub fn head_to_head(titles: Vec<String>, options: SearchOptions) {
+    let [a, b] = &titles[..] else {
+        panic!("compare needs exactly two shows, got {}", titles.len());
+    };
+
+    let roles = RoleFilter::new(&options.roles, &options.exclude_roles);
+
+    let db = Connection::open(Path::new("caterer.db")).expect("can create db");
+    rusqlite::vtab::array::load_module(&db).expect("vtab should load");
+    let source = Source::open(&db);
+
+    let show_a = source.fetch_show_record(resolve_show(&db, a), &roles);
+    let show_b = source.fetch_show_record(resolve_show(&db, b), &roles);
+
+    let mut shared: Vec<(&ShowPeepRecord, &ShowPeepRecord)> = show_a
+        .peeps
+        .values()
+        .filter_map(|peep_a| Some((peep_a, show_b.peeps.get(&peep_a.id)?)))
+        .collect();
+    shared.sort_by(|(a1, b1), (a2, b2)| {
+        (a2.score + b2.score)
+            .total_cmp(&(a1.score + b1.score))
+            .then(a1.name.cmp(&a2.name))
+    });
+
+    let ascii_bars = configure_styling(options.color, io::stdout().is_terminal());
+    let mut out = io::stdout().lock();
+
+    writeln!(
+        out,
+        "{} ({}) vs {} ({})",
+        style(&show_a.title).bold(),
+        show_a.start_year,
+        style(&show_b.title).bold(),
+        show_b.start_year
+    )
+    .unwrap();
+    if roles.is_active() {
+        writeln!(out, "Counting {roles}").unwrap();
+    }
+    writeln!(out, "{} shared people:", shared.len()).unwrap();
+    for (peep_a, peep_b) in &shared {
+        let bar = calc_overlap_bar(
+            ascii_bars,
+            job_color(best_job(&peep_a.jobs)),
+            job_color(best_job(&peep_b.jobs)),
+            show_a.episodes.len(),
+            peep_a.episode_count,
+            show_b.episodes.len(),
+            peep_b.episode_count,
+        );
+        let note = if !peep_a.focused || !peep_b.focused {
+            style(" outside the role focus").dim().to_string()
+        } else if !peep_a.stylistic || !peep_b.stylistic {
+            style(" not stylistic").dim().to_string()
+        } else {
+            String::new()
+        };
+        writeln!(
+            out,
+            "{bar} {}: {} → {}{note}",
+            peep_a.name,
+            side(peep_a, &show_a),
+            side(peep_b, &show_b)
+        )
+        .unwrap();
+    }
+
+    let staff: Vec<i64> = shared.iter().map(|(peep, _)| peep.id).collect();
+    let scorer = make_scorer(options.scorer, &source, &db, &staff);
+    let a_to_b = score_show_affinity(
+        slice::from_ref(&show_a),
+        &[],
+        show_b,
+        scorer.as_ref(),
+        &options.recency,
+        options.genre_weight,
+        options.explain,
+    );
+    let b_to_a = score_show_affinity(
+        slice::from_ref(&a_to_b.show),
+        &[],
+        show_a,
+        scorer.as_ref(),
+        &options.recency,
+        options.genre_weight,
+        options.explain,
+    );
+
+    write_score(&mut out, &b_to_a.show, &a_to_b);
+    write_score(&mut out, &a_to_b.show, &b_to_a);
+}
@@ -1,110 +1,325 @@
-use std::{collections::HashSet, path::Path, rc::Rc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 use compare::{score_show_affinity, ShowAffinity};
-use console::style;
+use diversity::diversify;
+use filter::ShowFilter;
+use franchise::{hide_franchises, FranchiseMode};
+use group::{group_by_person, GroupBy, PersonGroup};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use rusqlite::{types::Value, Connection};
-use show_tree::fetch_show_record;
+use recency::Recency;
+use render::{configure_styling, render, ColorChoice, OutputFormat, Results};
+use rusqlite::Connection;
+use scorer::{
+    root_weight, AdamicAdarScorer, CosineScorer, DefaultScorer, JaccardScorer, Scorer, ScorerKind,
+};
+use seeds::{person_seeds, read_seen, resolve_seed};
+use show_tree::ShowRecord;
+use source::{LinkedShow, Source};
+use taxonomy::RoleFilter;
+
+use crate::exit_with;
 
 mod compare;
+mod diversity;
+mod filter;
+mod franchise;
+mod group;
+mod head_to_head;
+mod path;
+mod ratings;
+mod recency;
+mod render;
+mod scorer;
+mod seeds;
 mod show_tree;
+mod source;
+mod taxonomy;
+mod why_not;
+
+pub use head_to_head::head_to_head;
+pub use path::path;
+pub use ratings::recommend;
+pub use recency::RecencyMode;
+pub use taxonomy::init as init_taxonomy;
+pub use why_not::why_not;
 
-#[derive(Debug, Clone)]
-struct ShowEntity {
-    id: i64,
-    title: String,
-    start_year: String,
-    parent_show_id: Option<i64>,
+pub struct SearchOptions {
+    /// Only the linked shows with the highest pre-score are hydrated. 0, the
+    /// default, hydrates them all.
+    pub max_candidates: usize,
+    /// How many results to print, after skipping `offset` of them.
+    pub limit: usize,
+    pub offset: usize,
+    /// Results scoring below this, after any `--not` penalties, are dropped.
+    pub min_score: f32,
+    pub format: OutputFormat,
+    /// Write results here instead of stdout. HTML reports always go to a file.
+    pub output: Option<PathBuf>,
+    pub color: ColorChoice,
+    pub scorer: ScorerKind,
+    /// Only people in these roles count, e.g. `writer,director`. Empty for all.
+    pub roles: Vec<String>,
+    pub exclude_roles: Vec<String>,
+    /// Show every term that went into each result's score.
+    pub explain: bool,
+    /// Seeds to steer away from: whatever a candidate scores against these
+    /// is taken off its score.
+    pub not_like: Vec<String>,
+    /// People whose series are added as seeds, by nconst or name.
+    pub people: Vec<String>,
+    /// Which linked shows are kept, applied before hydration.
+    pub filter: ShowFilter,
+    /// Lists of tconsts or IMDb ratings exports. These are never recommended.
+    pub seen: Vec<PathBuf>,
+    /// When above 0, seen series are also added as seeds with this weight, up
+    /// to 1.
+    pub seen_weight: f32,
+    /// From 0 for plain score order up to 1 to favor results sharing the
+    /// fewest people with those ranked above them.
+    pub diversity: f32,
+    /// Decays crew overlaps by how far apart in time the shows are.
+    pub recency: Recency,
+    /// How much genre overlap is blended into each score, from 0, the default,
+    /// to ignore genres up to 1 to zero out shows sharing none with the seeds.
+    pub genre_weight: f32,
+    /// Whether results from the same franchise as a seed are kept, grouped
+    /// into one or removed.
+    pub franchise: FranchiseMode,
+    /// List shows, or each seed person with the shows they also worked on.
+    pub group_by: GroupBy,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            max_candidates: 0,
+            limit: 100,
+            offset: 0,
+            min_score: 0.0,
+            format: OutputFormat::Console,
+            output: None,
+            color: ColorChoice::Auto,
+            scorer: ScorerKind::Default,
+            roles: vec![],
+            exclude_roles: vec![],
+            explain: false,
+            not_like: vec![],
+            people: vec![],
+            filter: ShowFilter::default(),
+            seen: vec![],
+            seen_weight: 0.0,
+            diversity: 0.0,
+            recency: Recency::default(),
+            genre_weight: 0.0,
+            franchise: FranchiseMode::Keep,
+            group_by: GroupBy::Show,
+        }
+    }
 }
 
-#[derive(Debug)]
-struct PrincipalEntity {
-    peep_id: i64,
-    show_id: i64,
-    category: String,
-    job: String,
-    name: String,
+/// Everything a search works out before results are paged and rendered.
+struct Ranking {
+    source: Source,
+    roles: RoleFilter,
+    seeds: Vec<ShowRecord>,
+    negatives: Vec<ShowRecord>,
+    /// From `--seen`, sorted.
+    seen: Vec<i64>,
+    /// Each stylistic seed person, with their best score on any seed.
+    staff: HashMap<i64, f32>,
+    /// Every linked show, best pre-score first, including any that were pruned.
+    linked: Vec<LinkedShow>,
+    /// How many of `linked` were hydrated and scored.
+    hydrated: usize,
+    scorer: Box<dyn Scorer>,
+    /// Best first, only those scoring at least `--min-score`.
+    affinities: Vec<ShowAffinity>,
 }
 
-pub fn search(titles: Vec<String>) {
+fn rank(titles: Vec<String>, options: &SearchOptions) -> Ranking {
+    let roles = RoleFilter::new(&options.roles, &options.exclude_roles);
+    if roles.is_active() {
+        eprintln!("----> Only counting {roles}");
+    }
+
     let db_path = Path::new("caterer.db");
     let db = Connection::open(db_path).expect("can create db");
     db.pragma_update(None, "foreign_keys", "ON").unwrap();
     rusqlite::vtab::array::load_module(&db).expect("vtab should load");
+    let source = Source::open(&db);
 
-    let show_ids_ints: Vec<i64> = titles
-        .into_iter()
-        .map(|title| title.trim_start_matches("tt").parse::<i64>().unwrap())
-        .collect();
+    let resolve = |seed: &String| resolve_seed(&db, seed).unwrap_or_else(|e| exit_with(&e));
+    let seed_ids: Vec<(i64, f32)> = titles.iter().map(resolve).collect();
+    let negative_ids: Vec<(i64, f32)> = options.not_like.iter().map(resolve).collect();
 
-    println!("----> Starting search for root shows");
+    eprintln!("----> Starting search for root shows");
     let start_q = Instant::now();
 
-    let shows: Vec<_> = show_ids_ints
-        .iter()
-        .map(|show_id| fetch_show_record((*show_id).into()))
-        .collect();
+    let fetch_seeds = |seeds: &[(i64, f32)]| -> Vec<ShowRecord> {
+        seeds
+            .iter()
+            .map(|(show_id, weight)| ShowRecord {
+                weight: *weight,
+                ..source.fetch_show_record(*show_id, &roles)
+            })
+            .collect()
+    };
+    let mut shows = fetch_seeds(&seed_ids);
+    for person in &options.people {
+        let person_shows =
+            person_seeds(&db, &source, person, &roles).unwrap_or_else(|e| exit_with(&e));
+        for show in person_shows {
+            if !shows.iter().any(|s| s.id == show.id) {
+                shows.push(show);
+            }
+        }
+    }
+    let named_seeds = shows.len();
+
+    let mut seen: Vec<i64> = options.seen.iter().flat_map(|p| read_seen(p)).collect();
+    seen.sort();
+    seen.dedup();
+    if options.seen_weight > 0.0 && !seen.is_empty() {
+        let mut seen_series: Vec<i64> = source
+            .show_meta(&db, &seen)
+            .into_iter()
+            .map(|meta| meta.id)
+            .filter(|id| !shows.iter().any(|s| s.id == *id))
+            .collect();
+        seen_series.sort();
+        for show_id in seen_series {
+            shows.push(ShowRecord {
+                weight: options.seen_weight,
+                ..source.fetch_show_record(show_id, &roles)
+            });
+        }
+    }
+    assert!(
+        !shows.is_empty(),
+        "Must supply at least one seed show, --person or --seen-weight"
+    );
+    let show_ids_ints: Vec<i64> = shows.iter().map(|s| s.id).collect();
 
     let end_q = Instant::now().duration_since(start_q);
     let per_q = end_q / shows.len() as u32;
 
-    for show in &shows {
-        println!(
-            "  • Found record for root show {} ({}) in {}ms",
+    for show in &shows[..named_seeds] {
+        eprintln!(
+            "  • Found record for root show {} ({}){} in {}ms",
             show.title,
             show.start_year,
+            root_weight(show.weight),
             per_q.as_millis()
         );
     }
 
-    let all_staff: HashSet<i64> = shows.iter().flat_map(|s| s.stylistic_peeps()).collect();
-    let all_staff: Rc<Vec<Value>> = Rc::new(all_staff.into_iter().map(Into::into).collect());
+    if !options.seen.is_empty() {
+        eprintln!("  • Read {} seen titles", seen.len());
+        if shows.len() > named_seeds {
+            eprintln!(
+                "  • Added {} seen series as seeds{}",
+                shows.len() - named_seeds,
+                root_weight(options.seen_weight)
+            );
+        }
+    }
+
+    let negatives = fetch_seeds(&negative_ids);
+    for show in &negatives {
+        eprintln!(
+            "  • Steering away from {} ({}){}",
+            show.title,
+            show.start_year,
+            root_weight(show.weight)
+        );
+    }
+
+    let all_staff = stylistic_staff(&shows);
 
-    println!(
+    eprintln!(
         "----> Found {} staff, starting search for linked shows",
         all_staff.len()
     );
 
-    let mut all_principals_q = db
-        .prepare(
-            "SELECT
-                    principals.show_id, principals.peep_id
-                FROM principals
-                JOIN peeps ON principals.peep_id = peeps.id
-                WHERE principals.peep_id
-                IN rarray(?1);",
-        )
-        .unwrap();
-    let all_show_and_episode_ids: Vec<_> = all_principals_q
-        .query_map([&all_staff], |row| Ok(row.get::<_, i64>(0)?))
-        .expect("search succeeds")
-        .filter_map(Result::ok)
+    let mut linked_shows: Vec<_> = source
+        .linked_shows(&db, &all_staff)
+        .into_iter()
+        .filter(|l| {
+            !show_ids_ints.contains(&l.id) && !negative_ids.iter().any(|(id, _)| *id == l.id)
+        })
         .collect();
 
-    let all_show_ids: HashSet<_> =
-        all_show_and_episode_ids
+    if !seen.is_empty() {
+        let before = linked_shows.len();
+        linked_shows.retain(|l| seen.binary_search(&l.id).is_err());
+        eprintln!(
+            "----> Dropped {} linked shows already seen",
+            before - linked_shows.len()
+        );
+    }
+
+    if options.filter.is_active() {
+        let before = linked_shows.len();
+        let ids: Vec<i64> = linked_shows.iter().map(|l| l.id).collect();
+        let allowed: HashSet<i64> = source
+            .show_meta(&db, &ids)
             .into_iter()
-            .map(|show_or_episode_id| {
-                let mut ep_q = db
-                    .prepare("SELECT id, parent_show_id FROM shows WHERE id=(?1);")
-                    .unwrap();
-                let show_id: i64 = ep_q
-                    .query_row([&show_or_episode_id], |r| {
-                        Ok((r.get::<_, Option<i64>>(1)?)
-                            .unwrap_or_else(|| r.get::<_, i64>(0).unwrap()))
-                    })
-                    .unwrap();
-
-                show_id
-            })
+            .filter(|meta| options.filter.allows(meta))
+            .map(|meta| meta.id)
             .collect();
-    let all_show_ids: Vec<_> = all_show_ids
-        .into_iter()
-        .filter(|l| !show_ids_ints.contains(l))
-        .collect();
+        linked_shows.retain(|l| allowed.contains(&l.id));
+        eprintln!(
+            "----> Kept {} of {before} linked shows: {}",
+            linked_shows.len(),
+            options.filter
+        );
+    }
+    let linked_count = linked_shows.len();
+
+    // Shows the --not seeds also link to lose those staff's weight, so they're
+    // the first to be pruned.
+    if !negatives.is_empty() {
+        let avoided: HashMap<i64, f32> = source
+            .linked_shows(&db, &stylistic_staff(&negatives))
+            .into_iter()
+            .map(|l| (l.id, l.weight))
+            .collect();
+        for linked in &mut linked_shows {
+            if let Some(weight) = avoided.get(&linked.id) {
+                linked.weight -= weight;
+            }
+        }
+    }
+
+    linked_shows.sort_by(|a, b| {
+        b.weight
+            .total_cmp(&a.weight)
+            .then(b.shared.cmp(&a.shared))
+            .then(a.id.cmp(&b.id))
+    });
+    let hydrated = if options.max_candidates > 0 {
+        linked_count.min(options.max_candidates)
+    } else {
+        linked_count
+    };
+    let all_show_ids: Vec<i64> = linked_shows[..hydrated].iter().map(|l| l.id).collect();
+
+    if all_show_ids.len() < linked_count {
+        eprintln!(
+            "----> Pruned {} of {linked_count} linked shows with the least shared staff (--max-candidates {})",
+            linked_count - all_show_ids.len(),
+            options.max_candidates
+        );
+    }
 
     let est = per_q * all_show_ids.len() as u32;
-    println!(
+    eprintln!(
         "----> Found {} linked shows, fetching full records. (estimated {} seconds)",
         all_show_ids.len(),
         est.as_secs()
@@ -114,58 +329,226 @@ pub fn search(titles: Vec<String>) {
 
     let candidate_shows: Vec<_> = all_show_ids
         .into_par_iter()
-        .map(|show_id| fetch_show_record(show_id.into()))
+        .map(|show_id| source.fetch_show_record(show_id, &roles))
         .collect();
 
     let end_q = Instant::now().duration_since(start_q);
 
-    println!("Loaded candidate shows in {}s", end_q.as_secs());
+    eprintln!("Loaded candidate shows in {}s", end_q.as_secs());
 
     let ignored_jobs: HashSet<&String> = shows
         .iter()
         .chain(candidate_shows.iter())
         .flat_map(|s| s.ignored_jobs())
         .collect();
-    println!("Ignoring the following jobs as non-stylistic:");
+    eprintln!("Ignoring the following jobs as non-stylistic:");
     for ij in ignored_jobs {
-        println!("  • {ij}");
+        eprintln!("  • {ij}");
     }
 
-    println!("----> Scoring shows");
+    eprintln!(
+        "----> Scoring shows with the {} scorer",
+        options.scorer.name()
+    );
+    if options.recency.is_active() {
+        eprintln!("----> Decaying crew overlaps, {}", options.recency);
+    }
 
     let start_q = Instant::now();
 
-    let mut show_affinities: Vec<ShowAffinity> = candidate_shows
+    let staff: Vec<i64> = all_staff.keys().copied().collect();
+    let scorer = make_scorer(options.scorer, &source, &db, &staff);
+
+    let show_affinities: Vec<ShowAffinity> = candidate_shows
         .into_iter()
-        .map(|cs| score_show_affinity(&shows[..], cs))
+        .map(|cs| {
+            score_show_affinity(
+                &shows[..],
+                &negatives,
+                cs,
+                scorer.as_ref(),
+                &options.recency,
+                options.genre_weight,
+                options.explain,
+            )
+        })
         .collect();
+    let mut show_affinities = rank_above_min(show_affinities, options.min_score);
 
-    show_affinities.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    if options.franchise != FranchiseMode::Keep {
+        let hidden;
+        (show_affinities, hidden) = hide_franchises(show_affinities, &shows, options.franchise);
+        let verb = if options.franchise == FranchiseMode::Group {
+            "Grouped"
+        } else {
+            "Removed"
+        };
+        eprintln!("----> {verb} {hidden} results from the same franchise as a seed");
+    }
 
-    let end_q = Instant::now().duration_since(start_q);
-    println!("Scored shows in {}s", end_q.as_secs());
-
-    println!("----> Top 100 shows:");
-
-    for affinity in show_affinities.into_iter().take(100) {
-        println!(
-            "\n\n### {} ({}) {}\nRating: {}\n{}: {}",
-            style(affinity.show.title).bold(),
-            affinity.show.start_year,
-            style(format!(
-                "https://www.imdb.com/title/tt{:07}",
-                affinity.show.id
-            ))
-            .dim(),
-            affinity
-                .show
-                .rating
-                .unwrap_or_else(|| "unknown".to_string()),
-            affinity.show.title_type,
-            affinity.show.genres,
+    if options.diversity > 0.0 {
+        let picks = options.offset + options.limit;
+        let ranked = show_affinities.len();
+        show_affinities = diversify(show_affinities, options.diversity, picks);
+        eprintln!(
+            "----> Re-ranked the top {} for diversity {}",
+            picks.min(ranked),
+            options.diversity
         );
-        for credit in affinity.credits {
-            println!("{} {}", credit.bar, credit.text);
+    }
+
+    let end_q = Instant::now().duration_since(start_q);
+    eprintln!("Scored shows in {}s", end_q.as_secs());
+
+    Ranking {
+        source,
+        roles,
+        seeds: shows,
+        negatives,
+        seen,
+        staff: all_staff,
+        linked: linked_shows,
+        hydrated,
+        scorer,
+        affinities: show_affinities,
+    }
+}
+
+/// Best first, with anything scoring under `min_score` dropped. Scores are
+/// after `--not` penalties, so a penalty can push a result out.
+fn rank_above_min(mut affinities: Vec<ShowAffinity>, min_score: f32) -> Vec<ShowAffinity> {
+    affinities.retain(|a| a.score >= min_score);
+    affinities.sort_by(|a, b| b.score.total_cmp(&a.score));
+    affinities
+}
+
+/// Adamic-Adar needs the degree of every person it might score.
+fn make_scorer(
+    kind: ScorerKind,
+    source: &Source,
+    db: &Connection,
+    staff: &[i64],
+) -> Box<dyn Scorer> {
+    match kind {
+        ScorerKind::Default => Box::new(DefaultScorer),
+        ScorerKind::Jaccard => Box::new(JaccardScorer),
+        ScorerKind::Cosine => Box::new(CosineScorer),
+        ScorerKind::AdamicAdar => Box::new(AdamicAdarScorer {
+            degrees: source.peep_degrees(db, staff),
+        }),
+    }
+}
+
+/// Each stylistic person on any of the shows, with their best score scaled
+/// by the show's seed weight.
+fn stylistic_staff(shows: &[ShowRecord]) -> HashMap<i64, f32> {
+    let mut staff: HashMap<i64, f32> = HashMap::new();
+    for show in shows {
+        for peep in show.peeps.values().filter(|p| p.stylistic) {
+            let weight = staff.entry(peep.id).or_default();
+            *weight = weight.max(peep.score * show.weight);
+        }
+    }
+    staff
+}
+
+pub fn search(titles: Vec<String>, options: SearchOptions) {
+    let Ranking {
+        seeds,
+        negatives,
+        roles,
+        staff,
+        affinities,
+        ..
+    } = rank(titles, &options);
+
+    let scored = affinities.len();
+    let (page, people, total) = match options.group_by {
+        GroupBy::Show => {
+            let end = scored.min(options.offset + options.limit);
+            (&affinities[options.offset.min(end)..end], None, scored)
+        }
+        GroupBy::Person => {
+            let people = group_by_person(&affinities[..scored], &staff);
+            let total = people.len();
+            let page: Vec<PersonGroup> = people
+                .into_iter()
+                .skip(options.offset)
+                .take(options.limit)
+                .collect();
+            (&[][..], Some(page), total)
         }
+    };
+
+    let output = options.output.clone().or_else(|| {
+        (options.format == OutputFormat::Html).then(|| PathBuf::from("caterer-report.html"))
+    });
+    let ascii_bars = configure_styling(
+        options.color,
+        output.is_none() && io::stdout().is_terminal(),
+    );
+    let results = Results {
+        seeds: &seeds,
+        negatives: &negatives,
+        total,
+        offset: options.offset,
+        page,
+        people: people.as_deref(),
+        roles: &roles,
+        ascii_bars,
+    };
+    match output {
+        Some(path) => {
+            let mut file = BufWriter::new(File::create(&path).expect("can create output file"));
+            render(options.format, &results, &mut file);
+            file.flush().expect("can write output file");
+            eprintln!("----> Wrote results to {}", path.display());
+        }
+        None => render(options.format, &results, &mut io::stdout().lock()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        compare::score_show_affinity, rank_above_min, recency::Recency, scorer::DefaultScorer,
+        show_tree::ShowRecord,
+    };
+
+    #[test]
+    fn not_penalties_can_push_results_under_the_minimum() {
+        let seeds = [ShowRecord::for_test(
+            1,
+            "Seed",
+            None,
+            &[(10, "director"), (11, "writer"), (12, "director")],
+        )];
+        let negatives = [ShowRecord::for_test(
+            2,
+            "Not",
+            None,
+            &[(10, "director"), (11, "writer")],
+        )];
+        let score = |id, credits| {
+            score_show_affinity(
+                &seeds,
+                &negatives,
+                ShowRecord::for_test(id, "Candidate", None, credits),
+                &DefaultScorer,
+                &Recency::default(),
+                0.0,
+                false,
+            )
+        };
+        let penalized = score(3, &[(10, "director"), (11, "writer")]);
+        let clean = score(4, &[(12, "director")]);
+
+        let min_score = clean.score;
+        assert!(penalized.base_score > min_score);
+        assert!(penalized.score < min_score);
+
+        let ranked = rank_above_min(vec![penalized, clean], min_score);
+        let ids: Vec<i64> = ranked.iter().map(|a| a.show.id).collect();
+        assert_eq!(ids, vec![4]);
     }
 }
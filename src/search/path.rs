@@ -0,0 +1,331 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+use console::style;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rusqlite::{Connection, OptionalExtension};
+
+use super::{
+    compare::{best_job, job_buff, normalize_job},
+    render::configure_styling,
+    seeds::{find_person, find_show},
+    show_tree::ShowRecord,
+    source::Source,
+    taxonomy::RoleFilter,
+    SearchOptions,
+};
+
+/// Either end of a path: a show, or a person.
+#[derive(Clone, Copy)]
+enum Endpoint {
+    Show(i64),
+    Person(i64),
+}
+
+fn exists(db: &Connection, table: &str, id: i64) -> bool {
+    db.query_row(
+        &format!("SELECT 1 FROM {table} WHERE id = ?1;"),
+        [id],
+        |_| Ok(()),
+    )
+    .optional()
+    .expect("search succeeds")
+    .is_some()
+}
+
+/// A tconst or nconst, or else a title, or else a name. `None` when nothing
+/// matches, including ids that aren't in the database.
+fn endpoint(db: &Connection, arg: &str) -> Option<Endpoint> {
+    if arg.starts_with("nm") {
+        if let Some(id) = find_person(db, arg) {
+            return exists(db, "peeps", id).then_some(Endpoint::Person(id));
+        }
+    }
+    if let Some(id) = find_show(db, arg) {
+        return (!arg.starts_with("tt") || exists(db, "shows", id)).then_some(Endpoint::Show(id));
+    }
+    find_person(db, arg).map(Endpoint::Person)
+}
+
+/// Everyone credited on a show, with their jobs on it.
+type Crew = HashMap<i64, Vec<String>>;
+
+/// A person links shows when they're stylistic and in the role focus on both.
+/// Their best job in the focus when they do.
+fn linking(crew: &Crew, peep_id: i64, roles: &RoleFilter) -> Option<String> {
+    let jobs: Vec<String> = crew
+        .get(&peep_id)?
+        .iter()
+        .filter(|j| roles.allows(j))
+        .cloned()
+        .collect();
+    if jobs.is_empty() {
+        return None;
+    }
+    let best = best_job(&jobs);
+    (job_buff(best) > 1.0).then(|| normalize_job(best).to_string())
+}
+
+/// One step into a show: from the previous show (or the start person when
+/// there's none) through a person, with their jobs on either side.
+struct Hop {
+    from: Option<i64>,
+    peep_id: i64,
+    from_job: String,
+    to_job: String,
+}
+
+/// Every shortest chain into `show_id`, stopping once there are `limit`.
+fn chains(reached: &HashMap<i64, Vec<Hop>>, show_id: i64, limit: usize) -> Vec<Vec<(&Hop, i64)>> {
+    let hops = &reached[&show_id];
+    if hops.is_empty() {
+        return vec![vec![]];
+    }
+
+    let mut found = vec![];
+    for hop in hops {
+        let before = match hop.from {
+            Some(from) => chains(reached, from, limit - found.len()),
+            None => vec![vec![]],
+        };
+        for mut chain in before {
+            chain.push((hop, show_id));
+            found.push(chain);
+            if found.len() == limit {
+                return found;
+            }
+        }
+    }
+    found
+}
+
+fn fetch(source: &Source, roles: &RoleFilter, mut ids: Vec<i64>) -> Vec<ShowRecord> {
+    ids.sort();
+    ids.into_par_iter()
+        .map(|show_id| source.fetch_show_record(show_id, roles))
+        .collect()
+}
+
+/// One step out from the frontier: every show any new person on it also
+/// worked on, reached through each of them that links it. Only crews are
+/// read along the way. Returns the shows reached for the first time.
+fn step(
+    db: &Connection,
+    source: &Source,
+    roles: &RoleFilter,
+    frontier: &[(i64, Crew)],
+    seen_people: &mut HashSet<i64>,
+    reached: &mut HashMap<i64, Vec<Hop>>,
+) -> Vec<(i64, Crew)> {
+    // Each new person once, with every frontier show they're on and their
+    // job there.
+    let mut people: HashMap<i64, Vec<(i64, String)>> = HashMap::new();
+    for (show_id, crew) in frontier {
+        for peep_id in crew.keys() {
+            if seen_people.contains(peep_id) {
+                continue;
+            }
+            if let Some(job) = linking(crew, *peep_id, roles) {
+                people.entry(*peep_id).or_default().push((*show_id, job));
+            }
+        }
+    }
+    seen_people.extend(people.keys());
+
+    let mut next: HashMap<i64, Vec<(i64, i64, &str)>> = HashMap::new();
+    for (peep_id, froms) in &people {
+        for show_id in source.peep_series(db, *peep_id) {
+            if !reached.contains_key(&show_id) {
+                next.entry(show_id).or_default().extend(
+                    froms
+                        .iter()
+                        .map(|(from, job)| (*from, *peep_id, job.as_str())),
+                );
+            }
+        }
+    }
+
+    let mut show_ids: Vec<i64> = next.keys().copied().collect();
+    show_ids.sort();
+    let mut shows = vec![];
+    for show_id in show_ids {
+        let crew = source.show_crew(db, show_id);
+        let hops: Vec<Hop> = next[&show_id]
+            .iter()
+            .filter_map(|(from, peep_id, from_job)| {
+                Some(Hop {
+                    from: Some(*from),
+                    peep_id: *peep_id,
+                    from_job: from_job.to_string(),
+                    to_job: linking(&crew, *peep_id, roles)?,
+                })
+            })
+            .collect();
+        if !hops.is_empty() {
+            reached.insert(show_id, hops);
+            shows.push((show_id, crew));
+        }
+    }
+    shows
+}
+
+/// Finds the shortest chains of people linking two shows or people, like a
+/// Bacon number for crews: each step goes from a show to another show
+/// through someone who worked on both. Only people in the `--roles` focus and
+/// stylistic jobs link shows. Gives up after `max_hops` people.
+pub fn path(from: String, to: String, max_hops: usize, options: SearchOptions) {
+    let roles = RoleFilter::new(&options.roles, &options.exclude_roles);
+    if roles.is_active() {
+        eprintln!("----> Only counting {roles}");
+    }
+
+    let db = Connection::open(Path::new("caterer.db")).expect("can create db");
+    rusqlite::vtab::array::load_module(&db).expect("vtab should load");
+    let source = Source::open(&db);
+
+    let (Some(start), Some(end)) = (endpoint(&db, &from), endpoint(&db, &to)) else {
+        for arg in [&from, &to] {
+            if endpoint(&db, arg).is_none() {
+                eprintln!("No show or person with the id, title or name {arg}");
+            }
+        }
+        return;
+    };
+
+    let mut reached: HashMap<i64, Vec<Hop>> = HashMap::new();
+    let mut seen_people: HashSet<i64> = HashSet::new();
+    let mut frontier: Vec<(i64, Crew)> = match start {
+        Endpoint::Show(show_id) => {
+            reached.insert(show_id, vec![]);
+            vec![(show_id, source.show_crew(&db, show_id))]
+        }
+        Endpoint::Person(peep_id) => {
+            seen_people.insert(peep_id);
+            let mut series = vec![];
+            for show_id in source.peep_series(&db, peep_id) {
+                let crew = source.show_crew(&db, show_id);
+                if let Some(job) = linking(&crew, peep_id, &roles) {
+                    let hop = Hop {
+                        from: None,
+                        peep_id,
+                        from_job: String::new(),
+                        to_job: job,
+                    };
+                    reached.insert(show_id, vec![hop]);
+                    series.push((show_id, crew));
+                }
+            }
+            series
+        }
+    };
+
+    let mut hops = 0;
+    // Each show a chain ends on, and for a person at the end their job on it.
+    let ends: Vec<(i64, Option<String>)> = loop {
+        let ends: Vec<(i64, Option<String>)> = frontier
+            .iter()
+            .filter_map(|(show_id, crew)| match end {
+                Endpoint::Show(end_id) => (*show_id == end_id).then_some((*show_id, None)),
+                Endpoint::Person(peep_id) => {
+                    Some((*show_id, Some(linking(crew, peep_id, &roles)?)))
+                }
+            })
+            .collect();
+        if !ends.is_empty() || hops == max_hops || frontier.is_empty() {
+            break ends;
+        }
+
+        hops += 1;
+        frontier = step(
+            &db,
+            &source,
+            &roles,
+            &frontier,
+            &mut seen_people,
+            &mut reached,
+        );
+        eprintln!(
+            "----> {hops} people out: {} new shows, {} people so far",
+            frontier.len(),
+            seen_people.len()
+        );
+    };
+
+    configure_styling(options.color, io::stdout().is_terminal());
+    let mut out = io::stdout().lock();
+
+    if ends.is_empty() {
+        if frontier.is_empty() {
+            writeln!(out, "No chain of people links {from} and {to}").unwrap();
+        } else {
+            writeln!(
+                out,
+                "No chain of up to {max_hops} people links {from} and {to} (--max-hops)"
+            )
+            .unwrap();
+        }
+        return;
+    }
+
+    let mut found = vec![];
+    for (end_id, tail) in &ends {
+        if found.len() == options.limit {
+            break;
+        }
+        for chain in chains(&reached, *end_id, options.limit - found.len()) {
+            found.push((chain, *end_id, tail));
+        }
+    }
+
+    // Only the shows on the chains being printed are read in full, for their
+    // titles and the names of the people linking them.
+    let mut show_ids: HashSet<i64> = found
+        .iter()
+        .flat_map(|(chain, end_id, _)| chain.iter().map(|(_, show_id)| *show_id).chain([*end_id]))
+        .collect();
+    if let Endpoint::Show(start_id) = start {
+        show_ids.insert(start_id);
+    }
+    let records: HashMap<i64, ShowRecord> = fetch(&source, &roles, show_ids.into_iter().collect())
+        .into_iter()
+        .map(|show| (show.id, show))
+        .collect();
+    let title = |show_id: &i64| {
+        let show = &records[show_id];
+        style(format!("{} ({})", show.title, show.start_year)).bold()
+    };
+    let name = |show_id: &i64, peep_id: i64| style(&records[show_id].peeps[&peep_id].name).cyan();
+
+    for (chain, end_id, tail) in found {
+        let mut line = String::new();
+        if let Endpoint::Show(start_id) = start {
+            line.push_str(&title(&start_id).to_string());
+        }
+        for (hop, show_id) in &chain {
+            let link = if hop.from.is_none() {
+                format!("{} —{}→ ", name(show_id, hop.peep_id), hop.to_job)
+            } else if hop.from_job == hop.to_job {
+                format!(" —{}, {}→ ", name(show_id, hop.peep_id), hop.to_job)
+            } else {
+                format!(
+                    " —{}, {}/{}→ ",
+                    name(show_id, hop.peep_id),
+                    hop.from_job,
+                    hop.to_job
+                )
+            };
+            line.push_str(&link);
+            line.push_str(&title(show_id).to_string());
+        }
+        if let (Some(job), Endpoint::Person(peep_id)) = (tail, end) {
+            line.push_str(&format!(
+                " —{job}→ {}",
+                records[&end_id].peeps[&peep_id].name
+            ));
+        }
+        writeln!(out, "{line}").unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use rusqlite::{types::Value, Connection};
+
+use super::{search, SearchOptions};
+
+/// Ratings at or above this make a series a seed, at or below `DISLIKED` a
+/// `--not` seed. Anything in between only counts as seen.
+const LIKED: f32 = 7.0;
+const DISLIKED: f32 = 4.0;
+
+/// Splits one CSV line, honoring quoted fields with `""` escapes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Every rated title in an IMDb ratings export, with its rating out of 10.
+fn read_ratings(path: &Path) -> Vec<(i64, f32)> {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Can't read ratings from {}: {e}", path.display()));
+    let mut lines = text.lines();
+    let header = csv_fields(lines.next().unwrap_or_default());
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim_start_matches('\u{feff}') == name)
+            .unwrap_or_else(|| panic!("{} has no {name} column", path.display()))
+    };
+    let (id_col, rating_col) = (column("Const"), column("Your Rating"));
+
+    lines
+        .filter_map(|line| {
+            let fields = csv_fields(line);
+            let id = fields.get(id_col)?.strip_prefix("tt")?.parse().ok()?;
+            let rating = fields.get(rating_col)?.parse().ok()?;
+            Some((id, rating))
+        })
+        .collect()
+}
+
+/// Recommends from a ratings export: series rated `LIKED` or more become
+/// seeds weighted from 0.25 at 7 up to 1 at 10, and series rated `DISLIKED`
+/// or less become `--not` seeds weighted from 0.25 at 4 up to 1 at 1. Every
+/// rated title counts as seen.
+pub fn recommend(mut titles: Vec<String>, ratings_path: PathBuf, mut options: SearchOptions) {
+    let ratings = read_ratings(&ratings_path);
+
+    let db = Connection::open(Path::new("caterer.db")).expect("can create db");
+    rusqlite::vtab::array::load_module(&db).expect("vtab should load");
+    let ids: Rc<Vec<Value>> = Rc::new(ratings.iter().map(|(id, _)| (*id).into()).collect());
+    let mut series_q = db
+        .prepare(
+            "SELECT id FROM shows
+            WHERE id IN rarray(?1) AND parent_show_id IS NULL
+            AND title_type IN ('tvSeries', 'tvMiniSeries');",
+        )
+        .unwrap();
+    let series: Vec<i64> = series_q
+        .query_map([&ids], |row| row.get(0))
+        .expect("search succeeds")
+        .filter_map(Result::ok)
+        .collect();
+
+    let mut liked = 0;
+    let mut disliked = 0;
+    for (id, rating) in ratings.iter().filter(|(id, _)| series.contains(id)) {
+        if *rating >= LIKED {
+            titles.push(format!("tt{id:07}:{}", (rating - LIKED + 1.0) / 4.0));
+            liked += 1;
+        } else if *rating <= DISLIKED {
+            options
+                .not_like
+                .push(format!("tt{id:07}:{}", (DISLIKED - rating + 1.0) / 4.0));
+            disliked += 1;
+        }
+    }
+    eprintln!(
+        "----> Rated {} titles, seeding from {liked} liked and {disliked} disliked series",
+        ratings.len()
+    );
+
+    options.seen.push(ratings_path);
+    search(titles, options);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::csv_fields;
+
+    #[test]
+    fn csv_fields_split_on_unquoted_commas() {
+        assert_eq!(csv_fields("tt1,8,2020-01-01"), ["tt1", "8", "2020-01-01"]);
+        assert_eq!(csv_fields(""), [""]);
+        assert_eq!(csv_fields("a,,b,"), ["a", "", "b", ""]);
+    }
+
+    #[test]
+    fn csv_fields_honor_quotes() {
+        assert_eq!(
+            csv_fields(r#"tt1,"Crime, Drama","The ""Wire""""#),
+            ["tt1", "Crime, Drama", r#"The "Wire""#]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{filter::YearRange, show_tree::ShowRecord};
+
+/// What a credit overlap's age is measured against.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RecencyMode {
+    #[default]
+    Off,
+    /// The gap between the root show and the candidate starting.
+    Gap,
+    /// How long ago the candidate started.
+    PreferRecent,
+    /// How far the candidate started outside these years.
+    Era(YearRange),
+}
+
+/// Optional time decay for crew overlaps: each root show's contribution to
+/// a candidate halves for every `half_life` years of distance. Shows without
+/// a start year aren't decayed.
+#[derive(Debug, Clone, Copy)]
+pub struct Recency {
+    pub mode: RecencyMode,
+    pub half_life: f32,
+}
+
+impl Default for Recency {
+    fn default() -> Self {
+        Self {
+            mode: RecencyMode::Off,
+            half_life: 10.0,
+        }
+    }
+}
+
+fn current_year() -> u32 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after the epoch")
+        .as_secs();
+    1970 + (secs / 31_556_952) as u32
+}
+
+impl Recency {
+    pub fn is_active(&self) -> bool {
+        self.mode != RecencyMode::Off
+    }
+
+    /// How much of a root show's contribution to the candidate is kept.
+    pub fn factor(&self, root_show: &ShowRecord, candidate_show: &ShowRecord) -> f32 {
+        let Ok(candidate_year) = candidate_show.start_year.parse::<u32>() else {
+            return 1.0;
+        };
+        let distance = match self.mode {
+            RecencyMode::Off => return 1.0,
+            RecencyMode::Gap => match root_show.start_year.parse::<u32>() {
+                Ok(root_year) => root_year.abs_diff(candidate_year),
+                Err(_) => return 1.0,
+            },
+            RecencyMode::PreferRecent => current_year().saturating_sub(candidate_year),
+            RecencyMode::Era(era) => {
+                era.from
+                    .map_or(0, |from| from.saturating_sub(candidate_year))
+                    + era.to.map_or(0, |to| candidate_year.saturating_sub(to))
+            }
+        };
+        0.5f32.powf(distance as f32 / self.half_life)
+    }
+}
+
+impl fmt::Display for Recency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let half_life = self.half_life;
+        match self.mode {
+            RecencyMode::Off => write!(f, "no decay"),
+            RecencyMode::Gap => write!(f, "halving every {half_life} years apart"),
+            RecencyMode::PreferRecent => write!(f, "halving every {half_life} years old"),
+            RecencyMode::Era(era) => write!(f, "halving every {half_life} years outside {era}"),
+        }
+    }
+}
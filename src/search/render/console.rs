@@ -0,0 +1,213 @@
+use std::io::{self, Write};
+
+use console::style;
+
+use super::Results;
+use crate::search::{
+    compare::{best_job, calc_overlap_bar, job_color, AffinityCredit},
+    group::{PersonGroup, PersonShow},
+};
+
+fn styled_jobs(jobs: &[String]) -> String {
+    style(
+        jobs.iter()
+            .map(|j| style(j).fg(job_color(j)).to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+    .cyan()
+    .to_string()
+}
+
+fn credit_line(credit: &AffinityCredit, ascii_bars: bool) -> String {
+    let name = &credit.name;
+    let title = style(&credit.root_show_title).bold().underlined();
+    let root_jobs = styled_jobs(&credit.root_jobs);
+    let candidate_jobs = styled_jobs(&credit.candidate_jobs);
+
+    let root_peep_eps = credit.root_episodes;
+    let root_eps = credit.root_episode_total;
+    let root_cred = if root_peep_eps > 0 {
+        format!("[{title}] {name}: {root_peep_eps}/{root_eps} ({root_jobs})")
+    } else {
+        format!("[{title}] {name}: ({root_jobs})")
+    };
+
+    let candidate_peep_eps = credit.candidate_episodes;
+    let candidate_eps = credit.candidate_episode_total;
+    let candidate_cred = if candidate_peep_eps > 0 {
+        format!("{candidate_peep_eps}/{candidate_eps} ({candidate_jobs})")
+    } else {
+        format!("({candidate_jobs})")
+    };
+
+    let bar = calc_overlap_bar(
+        ascii_bars,
+        job_color(best_job(&credit.root_jobs)),
+        job_color(best_job(&credit.candidate_jobs)),
+        root_eps,
+        root_peep_eps,
+        candidate_eps,
+        candidate_peep_eps,
+    );
+
+    format!("{bar} {root_cred} → {candidate_cred}")
+}
+
+/// One result a person worked on, as `#rank bar title (year) score → (jobs)`.
+fn person_show_line(show: &PersonShow, ascii_bars: bool) -> String {
+    let credit = show.credit;
+    let candidate = &show.affinity.show;
+    let bar = calc_overlap_bar(
+        ascii_bars,
+        job_color(best_job(&credit.root_jobs)),
+        job_color(best_job(&credit.candidate_jobs)),
+        credit.root_episode_total,
+        credit.root_episodes,
+        credit.candidate_episode_total,
+        credit.candidate_episodes,
+    );
+    let episodes = if credit.candidate_episodes > 0 {
+        format!(
+            "{}/{} ",
+            credit.candidate_episodes, credit.candidate_episode_total
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        "{:>4}  {bar} {} ({}) {:.2} → {episodes}({})",
+        format!("#{}", show.rank),
+        style(&candidate.title).bold(),
+        candidate.start_year,
+        show.affinity.score,
+        styled_jobs(&credit.candidate_jobs)
+    )
+}
+
+fn render_people(
+    people: &[PersonGroup],
+    total: usize,
+    offset: usize,
+    ascii_bars: bool,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if people.is_empty() {
+        writeln!(
+            out,
+            "----> No people in range (offset {offset}, {total} shared with the results)"
+        )?;
+    } else if offset == 0 {
+        writeln!(out, "----> Top {} of {total} people:", people.len())?;
+    } else {
+        writeln!(
+            out,
+            "----> People {} to {} of {total}:",
+            offset + 1,
+            offset + people.len()
+        )?;
+    }
+
+    for person in people {
+        let root_credits: Vec<String> = person
+            .root_credits
+            .iter()
+            .map(|(title, jobs)| format!("[{}] {}", style(title).bold(), styled_jobs(jobs)))
+            .collect();
+        writeln!(
+            out,
+            "\n\n### {} {}\n{}",
+            style(person.name).bold(),
+            style(format!("https://www.imdb.com/name/nm{:07}", person.peep_id)).dim(),
+            root_credits.join(", ")
+        )?;
+        for show in &person.shows {
+            writeln!(out, "{}", person_show_line(show, ascii_bars))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn render(results: &Results, out: &mut impl Write) -> io::Result<()> {
+    let Results {
+        negatives,
+        total,
+        offset,
+        page,
+        roles,
+        ascii_bars,
+        ..
+    } = *results;
+
+    if roles.is_active() {
+        writeln!(out, "----> Counting {roles}")?;
+    }
+    if !negatives.is_empty() {
+        let titles: Vec<&str> = negatives.iter().map(|s| s.title.as_str()).collect();
+        writeln!(out, "----> Not like {}", titles.join(", "))?;
+    }
+    if let Some(people) = results.people {
+        return render_people(people, total, offset, ascii_bars, out);
+    }
+
+    if page.is_empty() {
+        writeln!(
+            out,
+            "----> No shows in range (offset {offset}, {total} scored above the minimum)"
+        )?;
+    } else if offset == 0 {
+        writeln!(out, "----> Top {} of {total} shows:", page.len())?;
+    } else {
+        writeln!(
+            out,
+            "----> Shows {} to {} of {total}:",
+            offset + 1,
+            offset + page.len()
+        )?;
+    }
+
+    for affinity in page {
+        writeln!(
+            out,
+            "\n\n### {} ({}) {}\nRating: {}\n{}: {}",
+            style(&affinity.show.title).bold(),
+            affinity.show.start_year,
+            style(format!(
+                "https://www.imdb.com/title/tt{:07}",
+                affinity.show.id
+            ))
+            .dim(),
+            affinity.show.rating.as_deref().unwrap_or("unknown"),
+            affinity.show.title_type,
+            affinity.show.genres,
+        )?;
+        if !affinity.franchise.is_empty() {
+            let titles: Vec<String> = affinity
+                .franchise
+                .iter()
+                .map(|s| format!("{} ({})", s.title, s.start_year))
+                .collect();
+            writeln!(out, "Also in the franchise: {}", titles.join(", "))?;
+        }
+        for credit in &affinity.credits {
+            writeln!(out, "{}", credit_line(credit, ascii_bars))?;
+        }
+        if let Some(explanation) = &affinity.explanation {
+            writeln!(out, "{}", style("Score breakdown:").bold())?;
+            for term in &explanation.terms {
+                writeln!(out, "  + {:>12.2}  {}", term.value, term.label)?;
+            }
+            for multiplier in &explanation.multipliers {
+                writeln!(out, "  × {:>12.2}  {}", multiplier.value, multiplier.label)?;
+            }
+            for penalty in &explanation.penalties {
+                writeln!(out, "  − {:>12.2}  {}", penalty.value, penalty.label)?;
+            }
+            writeln!(out, "  = {:>12.2}", explanation.score)?;
+        }
+    }
+
+    Ok(())
+}
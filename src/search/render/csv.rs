@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+
+use super::{imdb_url, shared_people, Results};
+use crate::search::group::PersonGroup;
+
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// With `--group-by person`, one row per person and result they worked on.
+fn render_people(people: &[PersonGroup], offset: usize, out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "person_rank,nconst,name,rank,tconst,title,year,score,jobs,url"
+    )?;
+
+    for (i, person) in people.iter().enumerate() {
+        for show in &person.shows {
+            let row = [
+                (offset + i + 1).to_string(),
+                format!("nm{:07}", person.peep_id),
+                person.name.to_string(),
+                show.rank.to_string(),
+                format!("tt{:07}", show.affinity.show.id),
+                show.affinity.show.title.clone(),
+                show.affinity.show.start_year.clone(),
+                format!("{:.2}", show.affinity.score),
+                show.credit.candidate_jobs.join("; "),
+                imdb_url(show.affinity.show.id),
+            ];
+            writeln!(
+                out,
+                "{}",
+                row.iter().map(|v| field(v)).collect::<Vec<_>>().join(",")
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn render(results: &Results, out: &mut impl Write) -> io::Result<()> {
+    if let Some(people) = results.people {
+        return render_people(people, results.offset, out);
+    }
+
+    writeln!(
+        out,
+        "rank,tconst,title,year,type,genres,rating,score,shared_count,shared_people,url"
+    )?;
+
+    for (i, affinity) in results.page.iter().enumerate() {
+        let show = &affinity.show;
+        let people = shared_people(affinity);
+        let row = [
+            (results.offset + i + 1).to_string(),
+            format!("tt{:07}", show.id),
+            show.title.clone(),
+            show.start_year.clone(),
+            show.title_type.clone(),
+            show.genres.clone(),
+            show.rating.clone().unwrap_or_default(),
+            format!("{:.2}", affinity.score),
+            people.len().to_string(),
+            people.join("; "),
+            imdb_url(show.id),
+        ];
+        writeln!(
+            out,
+            "{}",
+            row.iter().map(|v| field(v)).collect::<Vec<_>>().join(",")
+        )?;
+    }
+
+    Ok(())
+}
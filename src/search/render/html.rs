@@ -0,0 +1,264 @@
+use std::io::{self, Write};
+
+use console::Color;
+
+use super::{imdb_url, Results};
+use crate::search::{
+    compare::{best_job, job_color, overlap_cells, AffinityCredit},
+    group::PersonGroup,
+};
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+h1 { font-size: 1.5rem; }
+a { color: #1a5fb4; }
+ol { padding-left: 2rem; }
+li { margin-bottom: 1.5rem; }
+h2 { font-size: 1.15rem; margin: 0 0 0.25rem; }
+.meta { color: #666; margin: 0 0 0.5rem; }
+summary { cursor: pointer; }
+table { border-collapse: collapse; margin-top: 0.5rem; font-size: 0.9rem; }
+td { padding: 0.15rem 0.5rem; vertical-align: middle; }
+.bar { display: flex; gap: 0.4rem; width: 12rem; }
+.half { display: flex; flex: 1; height: 0.7rem; background: #f3d6d6; }
+.half.left { justify-content: flex-end; }
+.jobs { color: #555; }
+";
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The 16 basic terminal colors, normal then bright.
+const BASIC: [&str; 16] = [
+    "#000000", "#c01c28", "#2e9e4f", "#d4a017", "#3465a4", "#a347ba", "#1b9aaa", "#d0cfcc",
+    "#5e5c64", "#f66151", "#33d17a", "#e9ad0c", "#2a7bde", "#c061cb", "#33c7de", "#ffffff",
+];
+
+fn css_color(color: Color) -> String {
+    match color {
+        Color::Black => BASIC[0].to_string(),
+        Color::Red => BASIC[1].to_string(),
+        Color::Green => BASIC[2].to_string(),
+        Color::Yellow => BASIC[3].to_string(),
+        Color::Blue => BASIC[4].to_string(),
+        Color::Magenta => BASIC[5].to_string(),
+        Color::Cyan => BASIC[6].to_string(),
+        Color::White => BASIC[7].to_string(),
+        Color::Color256(n @ 0..=15) => BASIC[n as usize].to_string(),
+        // The 6×6×6 color cube.
+        Color::Color256(n @ 16..=231) => {
+            let level = |c: u8| if c == 0 { 0 } else { 55 + c * 40 };
+            let n = n - 16;
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(n / 36),
+                level(n / 6 % 6),
+                level(n % 6)
+            )
+        }
+        // The grayscale ramp.
+        Color::Color256(n) => {
+            let gray = 8 + (n - 232) * 10;
+            format!("#{gray:02x}{gray:02x}{gray:02x}")
+        }
+    }
+}
+
+fn bar(credit: &AffinityCredit) -> String {
+    let (l_cells, r_cells) = overlap_cells(
+        credit.root_episode_total,
+        credit.root_episodes,
+        credit.candidate_episode_total,
+        credit.candidate_episodes,
+    );
+    let half = |side: &str, cells: usize, jobs: &[String]| {
+        format!(
+            r#"<div class="half {side}"><div style="width: {}%; background: {}"></div></div>"#,
+            cells * 10,
+            css_color(job_color(best_job(jobs)))
+        )
+    };
+
+    format!(
+        r#"<div class="bar">{}{}</div>"#,
+        half("left", l_cells, &credit.root_jobs),
+        half("right", r_cells, &credit.candidate_jobs)
+    )
+}
+
+fn credit_side(episodes: usize, total: usize, jobs: &[String]) -> String {
+    let jobs = escape(&jobs.join(", "));
+    if episodes > 0 {
+        format!(r#"{episodes}/{total} <span class="jobs">({jobs})</span>"#)
+    } else {
+        format!(r#"<span class="jobs">({jobs})</span>"#)
+    }
+}
+
+fn render_people(people: &[PersonGroup], offset: usize, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, r#"<ol start="{}">"#, offset + 1)?;
+    for person in people {
+        let root_credits: Vec<String> = person
+            .root_credits
+            .iter()
+            .map(|(title, jobs)| {
+                format!(
+                    r#"{} <span class="jobs">({})</span>"#,
+                    escape(title),
+                    escape(&jobs.join(", "))
+                )
+            })
+            .collect();
+        writeln!(
+            out,
+            r#"<li><h2><a href="https://www.imdb.com/name/nm{:07}">{}</a></h2>"#,
+            person.peep_id,
+            escape(person.name)
+        )?;
+        writeln!(
+            out,
+            r#"<p class="meta">On {}</p><table>"#,
+            root_credits.join(", ")
+        )?;
+        for show in &person.shows {
+            let credit = show.credit;
+            writeln!(
+                out,
+                r#"<tr><td>#{}</td><td>{}</td><td><a href="{}">{}</a> ({})</td><td>score {:.0}</td><td>{}</td></tr>"#,
+                show.rank,
+                bar(credit),
+                imdb_url(show.affinity.show.id),
+                escape(&show.affinity.show.title),
+                escape(&show.affinity.show.start_year),
+                show.affinity.score,
+                credit_side(
+                    credit.candidate_episodes,
+                    credit.candidate_episode_total,
+                    &credit.candidate_jobs
+                ),
+            )?;
+        }
+        writeln!(out, "</table></li>")?;
+    }
+    writeln!(out, "</ol>")
+}
+
+pub fn render(results: &Results, out: &mut impl Write) -> io::Result<()> {
+    let seeds: Vec<String> = results
+        .seeds
+        .iter()
+        .map(|s| format!(r#"<a href="{}">{}</a>"#, imdb_url(s.id), escape(&s.title)))
+        .collect();
+    let seed_titles: Vec<String> = results.seeds.iter().map(|s| escape(&s.title)).collect();
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, r#"<html lang="en"><head><meta charset="utf-8">"#)?;
+    writeln!(out, "<title>Shows like {}</title>", seed_titles.join(", "))?;
+    writeln!(out, "<style>{STYLE}</style></head><body>")?;
+    writeln!(out, "<h1>Shows like {}</h1>", seeds.join(", "))?;
+    if !results.negatives.is_empty() {
+        let negatives: Vec<String> = results
+            .negatives
+            .iter()
+            .map(|s| format!(r#"<a href="{}">{}</a>"#, imdb_url(s.id), escape(&s.title)))
+            .collect();
+        writeln!(
+            out,
+            r#"<p class="meta">Steering away from {}.</p>"#,
+            negatives.join(", ")
+        )?;
+    }
+    match results.people {
+        Some(people) => writeln!(
+            out,
+            r#"<p class="meta">People {} to {} of {} from the seeds, with the shows they also worked on.</p>"#,
+            results.offset + 1,
+            results.offset + people.len(),
+            results.total
+        )?,
+        None => writeln!(
+            out,
+            r#"<p class="meta">Shows {} to {} of {} that share stylistic crew with the seeds.</p>"#,
+            results.offset + 1,
+            results.offset + results.page.len(),
+            results.total
+        )?,
+    }
+    if results.roles.is_active() {
+        writeln!(
+            out,
+            r#"<p class="meta">Counting {}.</p>"#,
+            escape(&results.roles.to_string())
+        )?;
+    }
+    if let Some(people) = results.people {
+        render_people(people, results.offset, out)?;
+        return writeln!(out, "</body></html>");
+    }
+
+    writeln!(out, r#"<ol start="{}">"#, results.offset + 1)?;
+    for affinity in results.page {
+        let show = &affinity.show;
+        writeln!(
+            out,
+            r#"<li><h2><a href="{}">{}</a> ({})</h2>"#,
+            imdb_url(show.id),
+            escape(&show.title),
+            escape(&show.start_year)
+        )?;
+        writeln!(
+            out,
+            r#"<p class="meta">Rating {} · {} · {} · score {:.0}</p>"#,
+            escape(show.rating.as_deref().unwrap_or("unknown")),
+            escape(&show.title_type),
+            escape(&show.genres.replace(',', ", ")),
+            affinity.score
+        )?;
+        if !affinity.franchise.is_empty() {
+            let titles: Vec<String> = affinity
+                .franchise
+                .iter()
+                .map(|s| format!(r#"<a href="{}">{}</a>"#, imdb_url(s.id), escape(&s.title)))
+                .collect();
+            writeln!(
+                out,
+                r#"<p class="meta">Also in the franchise: {}.</p>"#,
+                titles.join(", ")
+            )?;
+        }
+
+        writeln!(
+            out,
+            "<details><summary>{} shared credits</summary><table>",
+            affinity.credits.len()
+        )?;
+        for credit in &affinity.credits {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>→ {}</td></tr>",
+                bar(credit),
+                escape(&credit.root_show_title),
+                escape(&credit.name),
+                credit_side(
+                    credit.root_episodes,
+                    credit.root_episode_total,
+                    &credit.root_jobs
+                ),
+                credit_side(
+                    credit.candidate_episodes,
+                    credit.candidate_episode_total,
+                    &credit.candidate_jobs
+                ),
+            )?;
+        }
+        writeln!(out, "</table></details></li>")?;
+    }
+    writeln!(out, "</ol></body></html>")?;
+
+    Ok(())
+}
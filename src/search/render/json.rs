@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use super::{imdb_url, Results};
+use crate::search::{
+    compare::AffinityCredit, group::PersonGroup, scorer::Explanation, show_tree::ShowRecord,
+};
+
+#[derive(Serialize)]
+struct JsonShow<'a> {
+    id: i64,
+    tconst: String,
+    url: String,
+    title: &'a str,
+    start_year: &'a str,
+    title_type: &'a str,
+    genres: Vec<&'a str>,
+    rating: Option<&'a str>,
+    votes: Option<i64>,
+    episode_count: usize,
+}
+
+#[derive(Serialize)]
+struct JsonAffinity<'a> {
+    rank: usize,
+    score: f32,
+    show: JsonShow<'a>,
+    credits: &'a [AffinityCredit],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<&'a Explanation>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    franchise: Vec<JsonShow<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonRootCredit<'a> {
+    title: &'a str,
+    jobs: &'a [String],
+}
+
+#[derive(Serialize)]
+struct JsonPersonShow<'a> {
+    rank: usize,
+    score: f32,
+    show: JsonShow<'a>,
+    credit: &'a AffinityCredit,
+}
+
+#[derive(Serialize)]
+struct JsonPerson<'a> {
+    id: i64,
+    nconst: String,
+    name: &'a str,
+    score: f32,
+    root_credits: Vec<JsonRootCredit<'a>>,
+    shows: Vec<JsonPersonShow<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonRoles<'a> {
+    include: &'a [String],
+    exclude: &'a [String],
+}
+
+#[derive(Serialize)]
+struct JsonResults<'a> {
+    seeds: Vec<JsonShow<'a>>,
+    not_like: Vec<JsonShow<'a>>,
+    roles: JsonRoles<'a>,
+    total: usize,
+    offset: usize,
+    results: Vec<JsonAffinity<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    people: Option<Vec<JsonPerson<'a>>>,
+}
+
+fn json_show(show: &ShowRecord) -> JsonShow<'_> {
+    JsonShow {
+        id: show.id,
+        tconst: format!("tt{:07}", show.id),
+        url: imdb_url(show.id),
+        title: &show.title,
+        start_year: &show.start_year,
+        title_type: &show.title_type,
+        genres: show.genres.split(',').filter(|g| *g != "\\N").collect(),
+        rating: show.rating.as_deref(),
+        votes: show.votes,
+        episode_count: show.episodes.len(),
+    }
+}
+
+fn json_person<'a>(person: &'a PersonGroup) -> JsonPerson<'a> {
+    JsonPerson {
+        id: person.peep_id,
+        nconst: format!("nm{:07}", person.peep_id),
+        name: person.name,
+        score: person.score,
+        root_credits: person
+            .root_credits
+            .iter()
+            .map(|&(title, jobs)| JsonRootCredit { title, jobs })
+            .collect(),
+        shows: person
+            .shows
+            .iter()
+            .map(|show| JsonPersonShow {
+                rank: show.rank,
+                score: show.affinity.score,
+                show: json_show(&show.affinity.show),
+                credit: show.credit,
+            })
+            .collect(),
+    }
+}
+
+pub fn render(results: &Results, out: &mut impl Write) -> io::Result<()> {
+    let json = JsonResults {
+        seeds: results.seeds.iter().map(json_show).collect(),
+        not_like: results.negatives.iter().map(json_show).collect(),
+        roles: JsonRoles {
+            include: results.roles.include(),
+            exclude: results.roles.exclude(),
+        },
+        total: results.total,
+        offset: results.offset,
+        results: results
+            .page
+            .iter()
+            .enumerate()
+            .map(|(i, affinity)| JsonAffinity {
+                rank: results.offset + i + 1,
+                score: affinity.score,
+                show: json_show(&affinity.show),
+                credits: &affinity.credits,
+                explanation: affinity.explanation.as_ref(),
+                franchise: affinity.franchise.iter().map(json_show).collect(),
+            })
+            .collect(),
+        people: results
+            .people
+            .map(|people| people.iter().map(json_person).collect()),
+    };
+
+    writeln!(
+        out,
+        "{}",
+        serde_json::to_string_pretty(&json).expect("results should serialize")
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::io::{self, Write};
+
+use super::{imdb_url, shared_people, Results};
+use crate::search::group::PersonGroup;
+
+/// Keeps titles and names from breaking out of a table cell or link.
+fn escape(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+fn episodes(count: usize, total: usize) -> String {
+    if count > 0 {
+        format!("{count}/{total} eps, ")
+    } else {
+        String::new()
+    }
+}
+
+fn render_people(people: &[PersonGroup], offset: usize, out: &mut impl Write) -> io::Result<()> {
+    for (i, person) in people.iter().enumerate() {
+        let root_credits: Vec<String> = person
+            .root_credits
+            .iter()
+            .map(|(title, jobs)| format!("{} ({})", escape(title), jobs.join(", ")))
+            .collect();
+        writeln!(
+            out,
+            "### {}. [{}](https://www.imdb.com/name/nm{:07})\n\nOn {}.\n",
+            offset + i + 1,
+            escape(person.name),
+            person.peep_id,
+            root_credits.join(", ")
+        )?;
+        for show in &person.shows {
+            let credit = show.credit;
+            writeln!(
+                out,
+                "- #{} [{}]({}) ({}), score {:.0}: {}{}",
+                show.rank,
+                escape(&show.affinity.show.title),
+                imdb_url(show.affinity.show.id),
+                show.affinity.show.start_year,
+                show.affinity.score,
+                episodes(credit.candidate_episodes, credit.candidate_episode_total),
+                credit.candidate_jobs.join(", "),
+            )?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+pub fn render(results: &Results, out: &mut impl Write) -> io::Result<()> {
+    let seeds: Vec<String> = results
+        .seeds
+        .iter()
+        .map(|s| format!("[{}]({})", escape(&s.title), imdb_url(s.id)))
+        .collect();
+    let negatives: Vec<String> = results
+        .negatives
+        .iter()
+        .map(|s| format!("[{}]({})", escape(&s.title), imdb_url(s.id)))
+        .collect();
+    if negatives.is_empty() {
+        writeln!(out, "## Shows like {}\n", seeds.join(", "))?;
+    } else {
+        writeln!(
+            out,
+            "## Shows like {} but not {}\n",
+            seeds.join(", "),
+            negatives.join(", ")
+        )?;
+    }
+    if results.roles.is_active() {
+        writeln!(out, "Counting {}.\n", results.roles)?;
+    }
+    if let Some(people) = results.people {
+        return render_people(people, results.offset, out);
+    }
+
+    writeln!(
+        out,
+        "| # | Title | Year | Type | Rating | Score | Shared people |"
+    )?;
+    writeln!(
+        out,
+        "|---|-------|------|------|--------|-------|---------------|"
+    )?;
+    for (i, affinity) in results.page.iter().enumerate() {
+        let show = &affinity.show;
+        writeln!(
+            out,
+            "| {} | [{}]({}) | {} | {} | {} | {:.0} | {} |",
+            results.offset + i + 1,
+            escape(&show.title),
+            imdb_url(show.id),
+            show.start_year,
+            show.title_type,
+            show.rating.as_deref().unwrap_or("unknown"),
+            affinity.score,
+            escape(&shared_people(affinity).join(", ")),
+        )?;
+    }
+
+    for (i, affinity) in results.page.iter().enumerate() {
+        let show = &affinity.show;
+        writeln!(
+            out,
+            "\n### {}. [{}]({}) ({})\n",
+            results.offset + i + 1,
+            escape(&show.title),
+            imdb_url(show.id),
+            show.start_year
+        )?;
+        if !affinity.franchise.is_empty() {
+            let titles: Vec<String> = affinity
+                .franchise
+                .iter()
+                .map(|s| format!("[{}]({})", escape(&s.title), imdb_url(s.id)))
+                .collect();
+            writeln!(out, "Also in the franchise: {}\n", titles.join(", "))?;
+        }
+        for credit in &affinity.credits {
+            writeln!(
+                out,
+                "- **{}**: {} ({}{}) → ({}{})",
+                escape(&credit.name),
+                escape(&credit.root_show_title),
+                episodes(credit.root_episodes, credit.root_episode_total),
+                credit.root_jobs.join(", "),
+                episodes(credit.candidate_episodes, credit.candidate_episode_total),
+                credit.candidate_jobs.join(", "),
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::{env, io::Write, str::FromStr};
+
+use ::console::Term;
+
+use super::{
+    compare::ShowAffinity, group::PersonGroup, show_tree::ShowRecord, taxonomy::RoleFilter,
+};
+
+mod console;
+mod csv;
+mod html;
+mod json;
+mod markdown;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Console,
+    Json,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "console" => Ok(OutputFormat::Console),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html),
+            _ => Err(format!(
+                "Unknown format {s}, expected console, json, csv, markdown or html"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "Unknown color choice {s}, expected auto, always or never"
+            )),
+        }
+    }
+}
+
+/// Decides once, before rendering, whether output gets ANSI styling and
+/// box-drawing bars. `auto` only styles a terminal, and `NO_COLOR` wins over
+/// it. Anything not going to a terminal gets plain ASCII bars unless colors
+/// are forced. Returns whether bars should be ASCII.
+pub fn configure_styling(choice: ColorChoice, to_terminal: bool) -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let colors = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            to_terminal && !no_color && Term::stdout().features().colors_supported()
+        }
+    };
+
+    ::console::set_colors_enabled(colors);
+    !to_terminal && choice != ColorChoice::Always
+}
+
+/// One page of ranked results, ready to hand to a renderer.
+pub struct Results<'a> {
+    pub seeds: &'a [ShowRecord],
+    /// The `--not` seeds.
+    pub negatives: &'a [ShowRecord],
+    /// How many shows scored above the minimum, before paging.
+    pub total: usize,
+    pub offset: usize,
+    pub page: &'a [ShowAffinity],
+    /// With `--group-by person`, the page of people in place of `page`, and
+    /// `total` counts people rather than shows.
+    pub people: Option<&'a [PersonGroup<'a>]>,
+    pub roles: &'a RoleFilter,
+    /// Draw overlap bars in ASCII, as decided by `configure_styling`.
+    pub ascii_bars: bool,
+}
+
+pub fn render(format: OutputFormat, results: &Results, out: &mut impl Write) {
+    match format {
+        OutputFormat::Console => console::render(results, out),
+        OutputFormat::Json => json::render(results, out),
+        OutputFormat::Csv => csv::render(results, out),
+        OutputFormat::Markdown => markdown::render(results, out),
+        OutputFormat::Html => html::render(results, out),
+    }
+    .expect("can write results");
+}
+
+fn imdb_url(show_id: i64) -> String {
+    format!("https://www.imdb.com/title/tt{show_id:07}")
+}
+
+/// Each shared person once, in credit order, however many root shows they
+/// overlap on.
+fn shared_people(affinity: &ShowAffinity) -> Vec<&str> {
+    let mut names: Vec<&str> = vec![];
+    for credit in &affinity.credits {
+        if !names.contains(&credit.name.as_str()) {
+            names.push(&credit.name);
+        }
+    }
+    names
+}
@@ -0,0 +1,442 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use serde::Serialize;
+
+use super::show_tree::{ShowPeepRecord, ShowRecord};
+
+/// Turns the overlap between the root shows and one candidate into a single
+/// number to rank by. Credits are collected the same way whichever scorer is
+/// used, only the score differs.
+pub trait Scorer: Sync {
+    /// Every term that went into the score, and the score itself. Each root
+    /// show's contribution is scaled by its entry in `root_weights`, its seed
+    /// weight times any recency decay.
+    fn explain(
+        &self,
+        root_shows: &[ShowRecord],
+        root_weights: &[f32],
+        candidate_show: &ShowRecord,
+    ) -> Explanation;
+}
+
+/// One labelled number in a score breakdown.
+#[derive(Serialize)]
+pub struct ExplainTerm {
+    pub label: String,
+    pub value: f32,
+}
+
+/// The terms are summed, then the sum is scaled by each multiplier in turn.
+/// Penalties for overlapping `--not` seeds are subtracted last.
+#[derive(Serialize)]
+pub struct Explanation {
+    pub terms: Vec<ExplainTerm>,
+    pub multipliers: Vec<ExplainTerm>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub penalties: Vec<ExplainTerm>,
+    pub score: f32,
+}
+
+impl Explanation {
+    fn summed(terms: Vec<ExplainTerm>) -> Self {
+        let score = terms.iter().map(|t| t.value).sum();
+        Self {
+            terms,
+            multipliers: vec![],
+            penalties: vec![],
+            score,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScorerKind {
+    Default,
+    Jaccard,
+    Cosine,
+    AdamicAdar,
+}
+
+impl FromStr for ScorerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(ScorerKind::Default),
+            "jaccard" => Ok(ScorerKind::Jaccard),
+            "cosine" => Ok(ScorerKind::Cosine),
+            "adamic-adar" => Ok(ScorerKind::AdamicAdar),
+            _ => Err(format!(
+                "Unknown scorer {s}, expected default, jaccard, cosine or adamic-adar"
+            )),
+        }
+    }
+}
+
+impl ScorerKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScorerKind::Default => "default",
+            ScorerKind::Jaccard => "jaccard",
+            ScorerKind::Cosine => "cosine",
+            ScorerKind::AdamicAdar => "adamic-adar",
+        }
+    }
+}
+
+fn stylistic_ids(show: &ShowRecord) -> HashSet<i64> {
+    show.peeps
+        .values()
+        .filter(|p| p.stylistic)
+        .map(|p| p.id)
+        .collect()
+}
+
+/// A root show's weight, for labels. Empty at the default of 1.
+pub fn root_weight(weight: f32) -> String {
+    if weight == 1.0 {
+        String::new()
+    } else {
+        let weight = format!("{weight:.2}");
+        format!(
+            " × weight {}",
+            weight.trim_end_matches('0').trim_end_matches('.')
+        )
+    }
+}
+
+fn peep_factors(peep: &ShowPeepRecord) -> String {
+    if peep.episode_share < 1.0 {
+        format!(
+            "{} {} × {:.2} of eps",
+            peep.role, peep.role_weight, peep.episode_share
+        )
+    } else {
+        format!("{} {}", peep.role, peep.role_weight)
+    }
+}
+
+/// The original formula: every shared person adds the product of their
+/// scores on both shows and the root show's weight, damped when the same
+/// name was already credited against an earlier root show. The sum is scaled
+/// by the candidate's rating squared, and by half the total weight of the
+/// root shows it overlaps when that's more than one show.
+pub struct DefaultScorer;
+
+impl Scorer for DefaultScorer {
+    fn explain(
+        &self,
+        root_shows: &[ShowRecord],
+        root_weights: &[f32],
+        candidate_show: &ShowRecord,
+    ) -> Explanation {
+        let mut terms: Vec<ExplainTerm> = vec![];
+        let mut credited: Vec<&String> = vec![];
+        let mut root_show_count = 0;
+        let mut root_show_weight = 0.0;
+        for (root_show, weight) in root_shows.iter().zip(root_weights) {
+            let mut has_stylistic_peep_overlap = false;
+
+            for root_peep in root_show.peeps.values() {
+                if let Some(candidate_peep) = candidate_show.peeps.get(&root_peep.id) {
+                    if !root_peep.focused || !candidate_peep.focused {
+                        continue;
+                    }
+                    let existing_credit_count =
+                        credited.iter().filter(|n| **n == &root_peep.name).count();
+                    let mut label = format!(
+                        "{} [{}] {} → {}{}",
+                        root_peep.name,
+                        root_show.title,
+                        peep_factors(root_peep),
+                        peep_factors(candidate_peep),
+                        root_weight(*weight)
+                    );
+                    let product = root_peep.score * candidate_peep.score * weight;
+                    let value = if existing_credit_count > 0 {
+                        let divisor = existing_credit_count + 2;
+                        label.push_str(&format!(" ÷ {divisor} (credited before)"));
+                        product / divisor as f32
+                    } else {
+                        product
+                    };
+                    if candidate_peep.stylistic && existing_credit_count == 0 {
+                        has_stylistic_peep_overlap = true;
+                    }
+                    credited.push(&root_peep.name);
+                    terms.push(ExplainTerm { label, value });
+                }
+            }
+
+            if has_stylistic_peep_overlap {
+                root_show_count += 1;
+                root_show_weight += weight;
+            }
+        }
+
+        let mut score: f32 = terms.iter().map(|t| t.value).sum();
+        let mut multipliers = vec![];
+
+        let parsed_rating: f32 = candidate_show
+            .rating
+            .as_ref()
+            .map(|r| r.parse().unwrap_or(5.0))
+            .unwrap_or(5.0);
+        multipliers.push(ExplainTerm {
+            label: format!("rating {parsed_rating}²"),
+            value: parsed_rating.powf(2.0),
+        });
+
+        if root_show_count > 1 {
+            let label = if root_show_weight == root_show_count as f32 {
+                format!("overlaps {root_show_count} root shows, ÷ 2")
+            } else {
+                format!("overlaps {root_show_count} root shows weighing {root_show_weight:.2}, ÷ 2")
+            };
+            multipliers.push(ExplainTerm {
+                label,
+                value: root_show_weight / 2.0,
+            });
+        }
+
+        for multiplier in &multipliers {
+            score *= multiplier.value;
+        }
+
+        Explanation {
+            terms,
+            multipliers,
+            penalties: vec![],
+            score,
+        }
+    }
+}
+
+/// Shared stylistic people over all stylistic people on either show, summed
+/// across root shows scaled by their weights.
+pub struct JaccardScorer;
+
+impl Scorer for JaccardScorer {
+    fn explain(
+        &self,
+        root_shows: &[ShowRecord],
+        root_weights: &[f32],
+        candidate_show: &ShowRecord,
+    ) -> Explanation {
+        let candidate = stylistic_ids(candidate_show);
+        Explanation::summed(
+            root_shows
+                .iter()
+                .zip(root_weights)
+                .map(|(root_show, weight)| {
+                    let root = stylistic_ids(root_show);
+                    let shared = root.intersection(&candidate).count();
+                    let union = root.union(&candidate).count();
+                    ExplainTerm {
+                        label: format!(
+                            "[{}] {shared} shared of {union} stylistic people{}",
+                            root_show.title,
+                            root_weight(*weight)
+                        ),
+                        value: if union == 0 {
+                            0.0
+                        } else {
+                            shared as f32 / union as f32 * weight
+                        },
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Cosine similarity between crew vectors, where each stylistic person's
+/// weight is their score on the show (role weight × episode share), summed
+/// across root shows scaled by their weights.
+pub struct CosineScorer;
+
+impl Scorer for CosineScorer {
+    fn explain(
+        &self,
+        root_shows: &[ShowRecord],
+        root_weights: &[f32],
+        candidate_show: &ShowRecord,
+    ) -> Explanation {
+        let vector = |show: &ShowRecord| -> HashMap<i64, f32> {
+            show.peeps
+                .values()
+                .filter(|p| p.stylistic)
+                .map(|p| (p.id, p.score))
+                .collect()
+        };
+        let norm = |v: &HashMap<i64, f32>| v.values().map(|w| w * w).sum::<f32>().sqrt();
+
+        let candidate = vector(candidate_show);
+        let candidate_norm = norm(&candidate);
+        Explanation::summed(
+            root_shows
+                .iter()
+                .zip(root_weights)
+                .map(|(root_show, weight)| {
+                    let root = vector(root_show);
+                    let shared: Vec<f32> = root
+                        .iter()
+                        .filter_map(|(id, w)| candidate.get(id).map(|c| w * c))
+                        .collect();
+                    let dot: f32 = shared.iter().sum();
+                    let norms = norm(&root) * candidate_norm;
+                    ExplainTerm {
+                        label: format!(
+                            "[{}] {dot:.1} over {} shared people ÷ norms {norms:.1}{}",
+                            root_show.title,
+                            shared.len(),
+                            root_weight(*weight)
+                        ),
+                        value: if norms == 0.0 {
+                            0.0
+                        } else {
+                            dot / norms * weight
+                        },
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Adamic-Adar: each shared stylistic person counts 1 / ln(shows they've
+/// worked on), so a prolific producer matters less than a rarely-seen DP.
+/// Summed across root shows scaled by their weights.
+pub struct AdamicAdarScorer {
+    /// How many distinct series each seed staff member is credited on.
+    pub degrees: HashMap<i64, usize>,
+}
+
+impl Scorer for AdamicAdarScorer {
+    fn explain(
+        &self,
+        root_shows: &[ShowRecord],
+        root_weights: &[f32],
+        candidate_show: &ShowRecord,
+    ) -> Explanation {
+        let candidate = stylistic_ids(candidate_show);
+        Explanation::summed(
+            root_shows
+                .iter()
+                .zip(root_weights)
+                .flat_map(|(root_show, &weight)| {
+                    root_show
+                        .peeps
+                        .values()
+                        .filter(|p| p.stylistic && candidate.contains(&p.id))
+                        .map(move |p| {
+                            let degree = self.degrees.get(&p.id).copied().unwrap_or(2).max(2);
+                            ExplainTerm {
+                                label: format!(
+                                    "{} [{}] 1 / ln({degree} shows){}",
+                                    p.name,
+                                    root_show.title,
+                                    root_weight(weight)
+                                ),
+                                value: weight / (degree as f32).ln(),
+                            }
+                        })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DefaultScorer, Scorer};
+    use crate::search::show_tree::ShowRecord;
+
+    /// The score from before scorers could be swapped: every shared person's
+    /// score product summed, anyone credited before divided down, times the
+    /// rating squared, times half the number of root shows with a stylistic
+    /// overlap when there's more than one.
+    fn baseline(root_shows: &[ShowRecord], candidate_show: &ShowRecord) -> f32 {
+        let mut score = 0.0;
+        let mut credited: Vec<&String> = vec![];
+        let mut root_show_count = 0;
+        for root_show in root_shows {
+            let mut has_stylistic_peep_overlap = false;
+            for root_peep in root_show.peeps.values() {
+                if let Some(candidate_peep) = candidate_show.peeps.get(&root_peep.id) {
+                    let existing_credit_count =
+                        credited.iter().filter(|n| **n == &root_peep.name).count();
+                    if existing_credit_count > 0 {
+                        score += (root_peep.score * candidate_peep.score)
+                            / (existing_credit_count + 2) as f32;
+                    } else {
+                        score += root_peep.score * candidate_peep.score;
+                    }
+                    if candidate_peep.stylistic && existing_credit_count == 0 {
+                        has_stylistic_peep_overlap = true;
+                    }
+                    credited.push(&root_peep.name);
+                }
+            }
+            if has_stylistic_peep_overlap {
+                root_show_count += 1;
+            }
+        }
+
+        let rating: f32 = candidate_show
+            .rating
+            .as_ref()
+            .map(|r| r.parse().unwrap_or(5.0))
+            .unwrap_or(5.0);
+        score *= rating.powf(2.0);
+        if root_show_count > 1 {
+            score *= root_show_count as f32 / 2.0;
+        }
+        score
+    }
+
+    #[test]
+    fn default_scorer_matches_the_baseline() {
+        let root_shows = [
+            ShowRecord::for_test(
+                1,
+                "Root A",
+                Some("8.5"),
+                &[(1, "writer"), (2, "composer"), (3, "actor"), (4, "editor")],
+            ),
+            ShowRecord::for_test(2, "Root B", None, &[(1, "writer"), (5, "director")]),
+            ShowRecord::for_test(3, "Root C", None, &[(6, "producer")]),
+        ];
+        let candidates = [
+            ShowRecord::for_test(
+                10,
+                "Both",
+                Some("7.9"),
+                &[
+                    (1, "created by"),
+                    (2, "composer"),
+                    (3, "actor"),
+                    (5, "director"),
+                ],
+            ),
+            ShowRecord::for_test(11, "One", Some("not a rating"), &[(4, "editor")]),
+            ShowRecord::for_test(12, "Unrated", None, &[(3, "actor"), (6, "producer")]),
+            ShowRecord::for_test(13, "None", Some("9.0"), &[(7, "writer")]),
+        ];
+
+        for candidate in &candidates {
+            let expected = baseline(&root_shows, candidate);
+            let scored = DefaultScorer
+                .explain(&root_shows, &[1.0; 3], candidate)
+                .score;
+            assert!(
+                (scored - expected).abs() <= expected.abs() * 1e-6,
+                "{}: {scored} vs {expected}",
+                candidate.title
+            );
+        }
+    }
+}
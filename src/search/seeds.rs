@@ -0,0 +1,258 @@
+use std::{fs, path::Path};
+
+use rusqlite::{Connection, OptionalExtension};
+
+use super::{show_tree::ShowRecord, source::Source, taxonomy::RoleFilter};
+
+/// Splits an optional `:weight` off a seed, e.g. `tt123:2`. Anything after the
+/// last colon that isn't a number is part of the title.
+fn split_weight(seed: &str) -> Result<(&str, f32), String> {
+    let Some((name, weight)) = seed.rsplit_once(':') else {
+        return Ok((seed, 1.0));
+    };
+    match weight.parse::<f32>() {
+        Ok(weight) if weight.is_finite() && weight > 0.0 => Ok((name, weight)),
+        Ok(_) => Err(format!(
+            "Seed weights must be finite and above 0, got {seed}"
+        )),
+        Err(_) => Ok((seed, 1.0)),
+    }
+}
+
+/// Looks up the whole seed with `find`, and only when that misses splits a
+/// `:weight` off it, so titles like `Star Trek: 1701` still resolve.
+/// `what` names what was looked for when nothing matches.
+fn resolve_weighted(
+    seed: &str,
+    what: &str,
+    find: impl Fn(&str) -> Option<i64>,
+) -> Result<(i64, f32), String> {
+    if let Some(id) = find(seed) {
+        return Ok((id, 1.0));
+    }
+    let (name, weight) = split_weight(seed)?;
+    find(name)
+        .map(|id| (id, weight))
+        .ok_or_else(|| format!("No {what} {name}"))
+}
+
+/// Resolves a seed as a tconst or a title, with an optional `:weight`.
+pub fn resolve_seed(db: &Connection, seed: &str) -> Result<(i64, f32), String> {
+    resolve_weighted(seed, "show with the tconst or title", |show| {
+        find_show(db, show)
+    })
+}
+
+/// Takes a tconst, or a title to look up. Of several shows with the same
+/// title, the best rated series wins.
+pub fn resolve_show(db: &Connection, show: &str) -> i64 {
+    find_show(db, show).unwrap_or_else(|| panic!("No show with the tconst or title {show}"))
+}
+
+pub fn find_show(db: &Connection, show: &str) -> Option<i64> {
+    if let Some(id) = show
+        .strip_prefix("tt")
+        .and_then(|id| id.parse::<i64>().ok())
+    {
+        return Some(id);
+    }
+
+    db.query_row(
+        "SELECT id FROM shows
+        WHERE title = ?1 COLLATE NOCASE AND parent_show_id IS NULL
+        ORDER BY title_type NOT IN ('tvSeries', 'tvMiniSeries'), CAST(rating AS REAL) DESC
+        LIMIT 1;",
+        [show],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Like `find_show`, but resolves an episode to its series and checks that a
+/// tconst is in the database.
+pub fn find_series(db: &Connection, show: &str) -> Option<i64> {
+    let id = find_show(db, show)?;
+    db.query_row(
+        "SELECT COALESCE(parent_show_id, id) FROM shows WHERE id = ?1;",
+        [id],
+        |row| row.get(0),
+    )
+    .optional()
+    .expect("search succeeds")
+}
+
+/// Takes an nconst, or a name to look up. Of several people with the same
+/// name, the one with the most credits wins.
+pub fn find_person(db: &Connection, person: &str) -> Option<i64> {
+    if let Some(id) = person
+        .strip_prefix("nm")
+        .and_then(|id| id.parse::<i64>().ok())
+    {
+        return Some(id);
+    }
+
+    db.query_row(
+        "SELECT peeps.id FROM peeps
+        JOIN principals ON principals.peep_id = peeps.id
+        WHERE peeps.name = ?1 COLLATE NOCASE
+        GROUP BY peeps.id
+        ORDER BY COUNT(*) DESC
+        LIMIT 1;",
+        [person],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Every series a `--person` worked on, as seeds. Each is weighted by their
+/// score on it (role weight × episode share) relative to their best, so the
+/// shows they shaped most count most. Shows where none of their jobs are in
+/// the `--roles` focus are left out.
+pub fn person_seeds(
+    db: &Connection,
+    source: &Source,
+    person: &str,
+    roles: &RoleFilter,
+) -> Result<Vec<ShowRecord>, String> {
+    let (peep_id, weight) = resolve_weighted(person, "person with the nconst or name", |p| {
+        find_person(db, p)
+    })?;
+
+    let mut series = source.peep_series(db, peep_id);
+    series.sort();
+    let mut shows: Vec<ShowRecord> = series
+        .into_iter()
+        .map(|show_id| source.fetch_show_record(show_id, roles))
+        .filter(|show| show.peeps.get(&peep_id).is_some_and(|p| p.focused))
+        .collect();
+
+    let best = shows
+        .iter()
+        .map(|s| s.peeps[&peep_id].score)
+        .fold(0.0, f32::max);
+    for show in &mut shows {
+        show.weight = if best > 0.0 {
+            weight * show.peeps[&peep_id].score / best
+        } else {
+            weight
+        };
+    }
+
+    match shows.first() {
+        Some(show) => eprintln!(
+            "  • Found {} series for {}",
+            shows.len(),
+            show.peeps[&peep_id].name
+        ),
+        None => eprintln!("  • Found no series for nm{peep_id:07} in the role focus"),
+    }
+
+    Ok(shows)
+}
+
+/// Reads a `--seen` list: one tconst per line, or an IMDb ratings export
+/// whose first column is the tconst. Lines that don't start with a tconst,
+/// like the CSV header, are skipped.
+pub fn read_seen(path: &Path) -> Vec<i64> {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Can't read seen titles from {}: {e}", path.display()));
+    text.lines()
+        .filter_map(|line| {
+            line.split([',', '\t', ' '])
+                .next()?
+                .trim_matches('"')
+                .strip_prefix("tt")?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use rusqlite::Connection;
+
+    use super::{find_series, read_seen, resolve_seed, split_weight};
+    use crate::db;
+
+    fn fixture() -> Connection {
+        let db = Connection::open_in_memory().unwrap();
+        db::create_tables(&db);
+        db.execute_batch(
+            "INSERT INTO shows (id, title, title_type, start_year, genres, rating, votes, parent_show_id)
+            VALUES
+                (1, 'Show A', 'tvSeries', '2001', 'Drama', '8.5', 1200, NULL),
+                (2, 'Pilot', 'tvEpisode', '2001', 'Drama', NULL, NULL, 1),
+                (3, 'Star Trek: 1701', 'tvSeries', '1966', 'Sci-Fi', '7.0', 300, NULL);",
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn weights_split_off_seeds() {
+        assert_eq!(split_weight("tt123:2"), Ok(("tt123", 2.0)));
+        assert_eq!(split_weight("The Wire:0.5"), Ok(("The Wire", 0.5)));
+        assert_eq!(split_weight("tt123"), Ok(("tt123", 1.0)));
+        assert_eq!(
+            split_weight("Star Trek: Voyager"),
+            Ok(("Star Trek: Voyager", 1.0))
+        );
+    }
+
+    #[test]
+    fn bad_weights_are_rejected() {
+        for seed in ["tt123:0", "tt123:-1", "tt123:NaN", "tt123:inf"] {
+            assert_eq!(
+                split_weight(seed),
+                Err(format!(
+                    "Seed weights must be finite and above 0, got {seed}"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn titles_ending_in_a_number_are_not_weights() {
+        let db = fixture();
+        assert_eq!(resolve_seed(&db, "Star Trek: 1701"), Ok((3, 1.0)));
+        assert_eq!(resolve_seed(&db, "Star Trek: 1701:2"), Ok((3, 2.0)));
+        assert_eq!(resolve_seed(&db, "Show A:2"), Ok((1, 2.0)));
+        assert_eq!(
+            resolve_seed(&db, "Show Z:2"),
+            Err("No show with the tconst or title Show Z".to_string())
+        );
+    }
+
+    #[test]
+    fn candidates_resolve_to_their_series() {
+        let db = fixture();
+        assert_eq!(find_series(&db, "tt0000001"), Some(1));
+        assert_eq!(find_series(&db, "show a"), Some(1));
+        assert_eq!(find_series(&db, "tt0000002"), Some(1));
+        assert_eq!(find_series(&db, "tt0000009"), None);
+        assert_eq!(find_series(&db, "Show Z"), None);
+    }
+
+    #[test]
+    fn seen_lists_and_ratings_exports_read() {
+        let path = env::temp_dir().join(format!("caterer-seen-{}.txt", process::id()));
+        fs::write(
+            &path,
+            "Const,Your Rating,Date Rated\n\
+            tt0000001\n\
+            \"tt0000002\",8,2020-01-01\n\
+            tt0000003\tThe Wire\n\
+            tt0000004 The Sopranos\n\
+            \n\
+            not a tconst\n",
+        )
+        .unwrap();
+        let seen = read_seen(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+    }
+}
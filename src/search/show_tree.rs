@@ -4,7 +4,11 @@ use std::{
     rc::Rc,
 };
 
-use super::compare::{best_job, job_buff};
+use super::{
+    compare::{best_job, job_buff, normalize_job},
+    taxonomy::RoleFilter,
+};
+use crate::index::Index;
 use rusqlite::{types::Value, Connection};
 
 #[derive(Debug)]
@@ -15,6 +19,14 @@ pub struct ShowPeepRecord {
     pub root_credit: bool,
     pub episode_count: usize,
     pub stylistic: bool,
+    /// Whether any of their jobs falls inside the `--roles` focus. People
+    /// outside it score nothing and are never credited.
+    pub focused: bool,
+    /// The canonical role their score comes from, its weight, and the share
+    /// of episodes (×4, capped at 1) it's scaled by. `score` is the product.
+    pub role: String,
+    pub role_weight: f32,
+    pub episode_share: f32,
     pub score: f32,
 }
 
@@ -26,18 +38,14 @@ pub struct ShowRecord {
     pub title_type: String,
     pub genres: String,
     pub rating: Option<String>,
+    pub votes: Option<i64>,
     pub peeps: HashMap<i64, ShowPeepRecord>,
     pub episodes: Vec<i64>,
+    /// How much a seed counts, from `tt123:2`. Always 1 for candidates.
+    pub weight: f32,
 }
 
 impl ShowRecord {
-    pub fn stylistic_peeps(&self) -> Vec<i64> {
-        self.peeps
-            .iter()
-            .filter_map(|(id, p)| if p.stylistic { Some(*id) } else { None })
-            .collect()
-    }
-
     pub fn ignored_jobs(&self) -> HashSet<&String> {
         self.peeps
             .values()
@@ -47,12 +55,43 @@ impl ShowRecord {
 }
 
 impl ShowRecord {
+    fn score_peeps(&mut self, roles: &RoleFilter) {
+        for show_peep in self.peeps.values_mut() {
+            let jobs: Vec<String> = show_peep
+                .jobs
+                .iter()
+                .filter(|j| roles.allows(j))
+                .cloned()
+                .collect();
+            if jobs.is_empty() {
+                continue;
+            }
+
+            let best = best_job(&jobs);
+            show_peep.focused = true;
+            show_peep.role = normalize_job(best).to_string();
+            show_peep.role_weight = job_buff(best);
+            show_peep.score = show_peep.role_weight;
+
+            if show_peep.score > 1.0 {
+                show_peep.stylistic = true;
+            }
+
+            if !self.episodes.is_empty() && show_peep.episode_count > 0 {
+                let proportion =
+                    ((show_peep.episode_count as f32 / self.episodes.len() as f32) * 4.0).min(1.0);
+                show_peep.episode_share = proportion;
+                show_peep.score *= proportion;
+            }
+        }
+    }
+
     fn hydrate_episodes(&mut self, db: &Connection) {
         let mut episode_q = db
             .prepare("SELECT id, parent_show_id FROM shows WHERE parent_show_id IN (?1);")
             .unwrap();
         self.episodes = episode_q
-            .query_map([&self.id], |row| Ok(row.get::<_, i64>(0)?))
+            .query_map([&self.id], |row| row.get::<_, i64>(0))
             .expect("search succeeds")
             .filter_map(Result::ok)
             .collect();
@@ -82,6 +121,10 @@ impl ShowRecord {
                         root_credit: true,
                         episode_count: 0,
                         stylistic: false,
+                        focused: false,
+                        role: String::new(),
+                        role_weight: 0.0,
+                        episode_share: 1.0,
                         score: 0.0,
                     },
                 ))
@@ -134,6 +177,10 @@ impl ShowRecord {
                     root_credit: false,
                     episode_count: 0,
                     stylistic: false,
+                    focused: false,
+                    role: String::new(),
+                    role_weight: 0.0,
+                    episode_share: 1.0,
                     score: 0.0,
                 });
 
@@ -148,15 +195,21 @@ impl ShowRecord {
     }
 }
 
-pub fn fetch_show_record(show_id: Value) -> ShowRecord {
+pub fn fetch_show_record(show_id: Value, roles: &RoleFilter) -> ShowRecord {
     let db_path = Path::new("caterer.db");
     let db = Connection::open(db_path).expect("can create db");
     db.pragma_update(None, "foreign_keys", "ON").unwrap();
     rusqlite::vtab::array::load_module(&db).expect("vtab should load");
 
+    show_record(&db, show_id, roles)
+}
+
+/// Reads and scores one show over an open connection, which needs the
+/// rarray module loaded.
+pub fn show_record(db: &Connection, show_id: Value, roles: &RoleFilter) -> ShowRecord {
     let mut show = db
         .query_row(
-            "SELECT id, title, start_year, title_type, genres, rating FROM shows WHERE id=(?1);",
+            "SELECT id, title, start_year, title_type, genres, rating, votes FROM shows WHERE id=(?1);",
             [&show_id],
             |row| {
                 Ok(ShowRecord {
@@ -166,30 +219,191 @@ pub fn fetch_show_record(show_id: Value) -> ShowRecord {
                     title_type: row.get(3)?,
                     genres: row.get(4)?,
                     rating: row.get(5)?,
+                    votes: row.get(6)?,
                     peeps: HashMap::new(),
                     episodes: vec![],
+                    weight: 1.0,
                 })
             },
         )
         .expect("Show ID should exist but does not");
 
-    show.hydrate_episodes(&db);
-    show.hydrate_direct_peeps(&db);
-    show.hydrate_episode_peeps(&db);
+    show.hydrate_episodes(db);
+    show.hydrate_direct_peeps(db);
+    show.hydrate_episode_peeps(db);
+    show.score_peeps(roles);
+
+    show
+}
+
+/// Builds the same record as `fetch_show_record`, read from the binary index.
+/// `None` for ids the index doesn't hold, which includes every episode.
+pub fn index_show_record(index: &Index, show_id: i64, roles: &RoleFilter) -> Option<ShowRecord> {
+    let indexed = index.show(show_id)?;
 
-    for show_peep in show.peeps.values_mut() {
-        show_peep.score = job_buff(best_job(&show_peep.jobs));
+    let mut show = ShowRecord {
+        id: indexed.id,
+        title: indexed.title.to_string(),
+        start_year: indexed.start_year.to_string(),
+        title_type: indexed.title_type.to_string(),
+        genres: indexed.genres.to_string(),
+        rating: indexed.rating.map(ToString::to_string),
+        votes: indexed.votes,
+        peeps: indexed
+            .credits
+            .into_iter()
+            .map(|c| {
+                (
+                    c.peep_id,
+                    ShowPeepRecord {
+                        id: c.peep_id,
+                        name: c.name.to_string(),
+                        jobs: c.jobs.into_iter().map(ToString::to_string).collect(),
+                        root_credit: c.root_credit,
+                        episode_count: c.episode_count,
+                        stylistic: false,
+                        focused: false,
+                        role: String::new(),
+                        role_weight: 0.0,
+                        episode_share: 1.0,
+                        score: 0.0,
+                    },
+                )
+            })
+            .collect(),
+        episodes: indexed.episodes,
+        weight: 1.0,
+    };
+    show.score_peeps(roles);
 
-        if show_peep.score > 1.0 {
-            show_peep.stylistic = true;
+    Some(show)
+}
+
+#[cfg(test)]
+impl ShowRecord {
+    /// A series with each `(peep_id, job)` credited on it directly, scored
+    /// with no role focus. Everyone is named `Person <id>`.
+    pub fn for_test(id: i64, title: &str, rating: Option<&str>, credits: &[(i64, &str)]) -> Self {
+        let mut peeps: HashMap<i64, ShowPeepRecord> = HashMap::new();
+        for &(peep_id, job) in credits {
+            peeps
+                .entry(peep_id)
+                .or_insert_with(|| ShowPeepRecord {
+                    id: peep_id,
+                    name: format!("Person {peep_id}"),
+                    jobs: vec![],
+                    root_credit: true,
+                    episode_count: 0,
+                    stylistic: false,
+                    focused: false,
+                    role: String::new(),
+                    role_weight: 0.0,
+                    episode_share: 1.0,
+                    score: 0.0,
+                })
+                .jobs
+                .push(job.to_string());
         }
+        let mut show = ShowRecord {
+            id,
+            title: title.to_string(),
+            start_year: "2000".to_string(),
+            title_type: "tvSeries".to_string(),
+            genres: String::new(),
+            rating: rating.map(ToString::to_string),
+            votes: None,
+            peeps,
+            episodes: vec![],
+            weight: 1.0,
+        };
+        show.score_peeps(&RoleFilter::new(&[], &[]));
+        show
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
 
-        if !show.episodes.is_empty() && show_peep.episode_count > 0 {
-            let proportion =
-                ((show_peep.episode_count as f32 / show.episodes.len() as f32) * 4.0).min(1.0);
-            show_peep.score *= proportion;
+    use rusqlite::Connection;
+
+    use super::{index_show_record, show_record, ShowRecord};
+    use crate::{
+        db,
+        index::{self, Index},
+        search::taxonomy::RoleFilter,
+    };
+
+    fn fixture() -> Connection {
+        let db = Connection::open_in_memory().unwrap();
+        rusqlite::vtab::array::load_module(&db).unwrap();
+        db::create_tables(&db);
+        db.execute_batch(
+            "INSERT INTO meta (key, value) VALUES ('build_stamp', 1);
+            INSERT INTO shows (id, title, title_type, start_year, genres, rating, votes, parent_show_id)
+            VALUES
+                (1, 'Show A', 'tvSeries', '2001', 'Crime,Drama', '8.5', 1200, NULL),
+                (2, 'Pilot', 'tvEpisode', '2001', 'Crime,Drama', NULL, NULL, 1),
+                (3, 'Second', 'tvEpisode', '2001', 'Crime,Drama', NULL, NULL, 1),
+                (4, 'Show B', 'tvMiniSeries', '2005', 'Comedy', NULL, NULL, NULL);
+            INSERT INTO peeps (id, name) VALUES (10, 'Writer One'), (11, 'Editor Two'), (12, 'Actor Three');
+            INSERT INTO principals (peep_id, show_id, category, job)
+            VALUES
+                (10, 1, 'writer', 'created by'),
+                (11, 2, 'editor', '\\N'),
+                (11, 3, 'editor', '\\N'),
+                (12, 2, 'actor', '\\N'),
+                (10, 4, 'writer', '\\N'),
+                (11, 4, 'editor', '\\N');",
+        )
+        .unwrap();
+        db
+    }
+
+    fn assert_same(from_db: &ShowRecord, from_index: &ShowRecord) {
+        assert_eq!(from_db.id, from_index.id);
+        assert_eq!(from_db.title, from_index.title);
+        assert_eq!(from_db.start_year, from_index.start_year);
+        assert_eq!(from_db.title_type, from_index.title_type);
+        assert_eq!(from_db.genres, from_index.genres);
+        assert_eq!(from_db.rating, from_index.rating);
+        assert_eq!(from_db.votes, from_index.votes);
+
+        let mut db_episodes = from_db.episodes.clone();
+        let mut index_episodes = from_index.episodes.clone();
+        db_episodes.sort();
+        index_episodes.sort();
+        assert_eq!(db_episodes, index_episodes);
+
+        assert_eq!(from_db.peeps.len(), from_index.peeps.len());
+        for (id, peep) in &from_db.peeps {
+            let indexed = &from_index.peeps[id];
+            assert_eq!(peep.name, indexed.name);
+            assert_eq!(peep.jobs, indexed.jobs);
+            assert_eq!(peep.root_credit, indexed.root_credit);
+            assert_eq!(peep.episode_count, indexed.episode_count);
+            assert_eq!(peep.role, indexed.role);
+            assert_eq!(peep.score, indexed.score);
         }
     }
 
-    show
+    #[test]
+    fn index_round_trips_show_records() {
+        let db = fixture();
+        let path = env::temp_dir().join(format!("caterer-roundtrip-{}.idx", process::id()));
+        index::export_to(&db, &path);
+        let index = Index::open(&path).expect("exported index should open");
+        let roles = RoleFilter::new(&[], &[]);
+
+        for show_id in [1, 4] {
+            let from_index = index_show_record(&index, show_id, &roles).unwrap();
+            assert_same(&show_record(&db, show_id.into(), &roles), &from_index);
+        }
+        assert_eq!(show_record(&db, 1.into(), &roles).episodes.len(), 2);
+        // Episodes are left to SQLite.
+        assert!(index_show_record(&index, 2, &roles).is_none());
+
+        drop(index);
+        fs::remove_file(&path).unwrap();
+    }
 }
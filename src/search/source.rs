@@ -0,0 +1,262 @@
+use std::{collections::HashMap, rc::Rc};
+
+use rusqlite::{params, types::Value, Connection};
+
+use super::{
+    filter::ShowMeta,
+    show_tree::{fetch_show_record, index_show_record, ShowRecord},
+    taxonomy::RoleFilter,
+};
+use crate::{
+    db, exit_with,
+    index::{Index, INDEX_PATH},
+};
+
+pub struct LinkedShow {
+    pub id: i64,
+    pub shared: usize,
+    pub weight: f32,
+}
+
+/// Where show records come from: the binary index when it matches the
+/// database, otherwise SQLite directly.
+pub enum Source {
+    Sqlite,
+    Index(Index),
+}
+
+impl Source {
+    pub fn open(db: &Connection) -> Self {
+        if !db::has_votes(db) {
+            exit_with(
+                "caterer.db is from before vote counts, run export-index to add them or build to rebuild it",
+            );
+        }
+        match Index::open_matching(db) {
+            Some(index) => {
+                eprintln!("----> Reading shows from {INDEX_PATH}");
+                Source::Index(index)
+            }
+            None => Source::Sqlite,
+        }
+    }
+
+    pub fn fetch_show_record(&self, show_id: i64, roles: &RoleFilter) -> ShowRecord {
+        match self {
+            Source::Sqlite => fetch_show_record(show_id.into(), roles),
+            // Episodes aren't in the index, SQLite still has them.
+            Source::Index(index) => index_show_record(index, show_id, roles)
+                .unwrap_or_else(|| fetch_show_record(show_id.into(), roles)),
+        }
+    }
+
+    /// Every series (episodes resolved to their parent) that any of the staff
+    /// are credited on, with a cheap pre-score of how many staff it shares
+    /// and the sum of their weights.
+    pub fn linked_shows(&self, db: &Connection, staff: &HashMap<i64, f32>) -> Vec<LinkedShow> {
+        match self {
+            Source::Index(index) => {
+                let mut linked: HashMap<i64, LinkedShow> = HashMap::new();
+                for (peep_id, weight) in staff {
+                    for show_id in index.peep_show_ids(*peep_id) {
+                        let l = linked.entry(show_id).or_insert(LinkedShow {
+                            id: show_id,
+                            shared: 0,
+                            weight: 0.0,
+                        });
+                        l.shared += 1;
+                        l.weight += weight;
+                    }
+                }
+                linked.into_values().collect()
+            }
+            Source::Sqlite => {
+                db.execute_batch(
+                    "DROP TABLE IF EXISTS temp.seed_staff;
+                    CREATE TEMP TABLE seed_staff (peep_id INTEGER PRIMARY KEY, weight REAL);",
+                )
+                .unwrap();
+                {
+                    let mut insert_q = db
+                        .prepare("INSERT INTO temp.seed_staff (peep_id, weight) VALUES (?1, ?2);")
+                        .unwrap();
+                    for (peep_id, weight) in staff {
+                        insert_q.execute(params![peep_id, weight]).unwrap();
+                    }
+                }
+
+                let mut linked_q = db
+                    .prepare(
+                        "SELECT series, COUNT(*), SUM(weight) FROM (
+                            SELECT DISTINCT
+                                COALESCE(shows.parent_show_id, shows.id) AS series,
+                                seed_staff.peep_id,
+                                seed_staff.weight
+                            FROM principals
+                            JOIN temp.seed_staff ON principals.peep_id = seed_staff.peep_id
+                            JOIN shows ON principals.show_id = shows.id
+                        )
+                        GROUP BY series;",
+                    )
+                    .unwrap();
+                linked_q
+                    .query_map([], |row| {
+                        Ok(LinkedShow {
+                            id: row.get(0)?,
+                            shared: row.get(1)?,
+                            weight: row.get(2)?,
+                        })
+                    })
+                    .expect("search succeeds")
+                    .filter_map(Result::ok)
+                    .collect()
+            }
+        }
+    }
+
+    /// The filterable fields of each series, without hydrating any of them.
+    /// Episodes and unknown ids are left out.
+    pub fn show_meta(&self, db: &Connection, show_ids: &[i64]) -> Vec<ShowMeta> {
+        match self {
+            Source::Index(index) => show_ids
+                .iter()
+                .filter_map(|id| {
+                    index.show_meta(*id).map(|meta| ShowMeta {
+                        id: *id,
+                        title_type: meta.title_type.to_string(),
+                        start_year: meta.start_year.to_string(),
+                        genres: meta.genres.to_string(),
+                        rating: meta.rating.map(ToString::to_string),
+                        votes: meta.votes,
+                    })
+                })
+                .collect(),
+            Source::Sqlite => {
+                let show_ids: Rc<Vec<Value>> =
+                    Rc::new(show_ids.iter().cloned().map(Into::into).collect());
+                let mut meta_q = db
+                    .prepare(
+                        "SELECT id, title_type, start_year, genres, rating, votes
+                        FROM shows WHERE id IN rarray(?1) AND parent_show_id IS NULL;",
+                    )
+                    .unwrap();
+                meta_q
+                    .query_map([&show_ids], |row| {
+                        Ok(ShowMeta {
+                            id: row.get(0)?,
+                            title_type: row.get(1)?,
+                            start_year: row.get(2)?,
+                            genres: row.get(3)?,
+                            rating: row.get(4)?,
+                            votes: row.get(5)?,
+                        })
+                    })
+                    .expect("search succeeds")
+                    .filter_map(Result::ok)
+                    .collect()
+            }
+        }
+    }
+
+    /// Every series (episodes resolved to their parent) a person is credited on.
+    pub fn peep_series(&self, db: &Connection, peep_id: i64) -> Vec<i64> {
+        match self {
+            Source::Index(index) => index.peep_show_ids(peep_id),
+            Source::Sqlite => {
+                let mut series_q = db
+                    .prepare(
+                        "SELECT DISTINCT COALESCE(shows.parent_show_id, shows.id)
+                        FROM principals
+                        JOIN shows ON principals.show_id = shows.id
+                        WHERE principals.peep_id = ?1;",
+                    )
+                    .unwrap();
+                series_q
+                    .query_map([peep_id], |row| row.get(0))
+                    .expect("search succeeds")
+                    .filter_map(Result::ok)
+                    .collect()
+            }
+        }
+    }
+
+    /// The jobs of everyone credited on a show or its episodes, without
+    /// reading anything else about it.
+    pub fn show_crew(&self, db: &Connection, show_id: i64) -> HashMap<i64, Vec<String>> {
+        if let Source::Index(index) = self {
+            // Episodes aren't in the index, SQLite still has them.
+            if let Some(show) = index.show(show_id) {
+                return show
+                    .credits
+                    .into_iter()
+                    .map(|c| {
+                        (
+                            c.peep_id,
+                            c.jobs.into_iter().map(ToString::to_string).collect(),
+                        )
+                    })
+                    .collect();
+            }
+        }
+
+        let mut crew_q = db
+            .prepare(
+                "SELECT principals.peep_id, principals.category, principals.job
+                FROM principals
+                JOIN shows ON principals.show_id = shows.id
+                WHERE shows.id = ?1 OR shows.parent_show_id = ?1
+                ORDER BY shows.id != ?1, principals.id;",
+            )
+            .unwrap();
+        let mut crew: HashMap<i64, Vec<String>> = HashMap::new();
+        let credits = crew_q
+            .query_map([show_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .expect("search succeeds")
+            .filter_map(Result::ok);
+        for (peep_id, category, job) in credits {
+            let jobs = crew.entry(peep_id).or_default();
+            for job in [category, job] {
+                if job != "\\N" && !jobs.contains(&job) {
+                    jobs.push(job);
+                }
+            }
+        }
+        crew
+    }
+
+    /// How many distinct series each person is credited on.
+    pub fn peep_degrees(&self, db: &Connection, peep_ids: &[i64]) -> HashMap<i64, usize> {
+        match self {
+            Source::Index(index) => peep_ids
+                .iter()
+                .map(|id| (*id, index.peep_show_ids(*id).len()))
+                .collect(),
+            Source::Sqlite => {
+                let peep_ids: Rc<Vec<Value>> =
+                    Rc::new(peep_ids.iter().cloned().map(Into::into).collect());
+                let mut degree_q = db
+                    .prepare(
+                        "SELECT
+                            principals.peep_id,
+                            COUNT(DISTINCT COALESCE(shows.parent_show_id, shows.id))
+                        FROM principals
+                        JOIN shows ON principals.show_id = shows.id
+                        WHERE principals.peep_id IN rarray(?1)
+                        GROUP BY principals.peep_id;",
+                    )
+                    .unwrap();
+                degree_q
+                    .query_map([&peep_ids], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .expect("search succeeds")
+                    .filter_map(Result::ok)
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::HashMap, fmt, fs, path::Path, sync::OnceLock};
+
+use console::Color;
+
+use super::compare::normalize_job;
+use serde::Deserialize;
+
+/// The built-in job taxonomy. Also the template for a `--weights` file, any
+/// part of which can be left out to keep these values.
+const BUILT_IN: &str = include_str!("taxonomy.json");
+
+static TAXONOMY: OnceLock<Taxonomy> = OnceLock::new();
+
+#[derive(Deserialize)]
+struct RoleRule {
+    role: String,
+    patterns: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TaxonomyFile {
+    rules: Option<Vec<RoleRule>>,
+    weights: Option<HashMap<String, f32>>,
+    colors: Option<HashMap<String, String>>,
+    default_weight: Option<f32>,
+    default_color: Option<String>,
+}
+
+/// Maps raw IMDb categories and jobs onto canonical roles, and gives each
+/// role a weight and a display color.
+pub struct Taxonomy {
+    /// Checked in order, the first rule with any pattern contained in the
+    /// lowercased job wins.
+    rules: Vec<RoleRule>,
+    weights: HashMap<String, f32>,
+    colors: HashMap<String, Color>,
+    default_weight: f32,
+    default_color: Color,
+}
+
+fn parse_color(name: &str) -> Color {
+    match name {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        n => Color::Color256(
+            n.parse()
+                .unwrap_or_else(|_| panic!("Unknown color {n}, expected a color name or 0-255")),
+        ),
+    }
+}
+
+fn parse_file(json: &str, source: &str) -> TaxonomyFile {
+    serde_json::from_str(json).unwrap_or_else(|e| panic!("Invalid weights in {source}: {e}"))
+}
+
+impl Taxonomy {
+    fn built_in() -> Self {
+        let mut taxonomy = Self {
+            rules: vec![],
+            weights: HashMap::new(),
+            colors: HashMap::new(),
+            default_weight: 1.0,
+            default_color: Color::Red,
+        };
+        taxonomy.apply(parse_file(BUILT_IN, "built-in taxonomy"));
+        taxonomy
+    }
+
+    /// Rules replace the built-in list wholesale, since their order matters.
+    /// Weights and colors are merged role by role.
+    fn apply(&mut self, file: TaxonomyFile) {
+        if let Some(rules) = file.rules {
+            self.rules = rules
+                .into_iter()
+                .map(|r| RoleRule {
+                    role: r.role,
+                    patterns: r.patterns.iter().map(|p| p.to_lowercase()).collect(),
+                })
+                .collect();
+        }
+        self.weights.extend(file.weights.unwrap_or_default());
+        self.colors.extend(
+            file.colors
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(role, color)| (role, parse_color(&color))),
+        );
+        if let Some(weight) = file.default_weight {
+            self.default_weight = weight;
+        }
+        if let Some(color) = file.default_color {
+            self.default_color = parse_color(&color);
+        }
+    }
+
+    pub fn normalize(&self, job: &str) -> Option<&str> {
+        let job = job.to_lowercase();
+        self.rules
+            .iter()
+            .find(|rule| rule.patterns.iter().any(|p| job.contains(p.as_str())))
+            .map(|rule| rule.role.as_str())
+    }
+
+    /// Whether a role name means anything here: it matches a rule, or has
+    /// its own weight.
+    pub fn knows(&self, role: &str) -> bool {
+        self.normalize(role).is_some() || self.weights.contains_key(&role.to_lowercase())
+    }
+
+    /// Every canonical role and weighted job, for error messages.
+    fn role_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .rules
+            .iter()
+            .map(|r| r.role.as_str())
+            .chain(self.weights.keys().map(String::as_str))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn weight(&self, role: &str) -> f32 {
+        self.weights
+            .get(role)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+
+    pub fn color(&self, role: &str) -> Color {
+        self.colors.get(role).copied().unwrap_or(self.default_color)
+    }
+}
+
+pub fn taxonomy() -> &'static Taxonomy {
+    TAXONOMY.get_or_init(Taxonomy::built_in)
+}
+
+/// Builds the taxonomy once, with an optional `--weights` file layered over
+/// the built-in one. Called before anything is scored; if the taxonomy is
+/// already in use it's kept as it is.
+pub fn init(weights: Option<&Path>) {
+    let Some(path) = weights else {
+        taxonomy();
+        return;
+    };
+    let json = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Can't read weights from {}: {e}", path.display()));
+    let mut taxonomy = Taxonomy::built_in();
+    taxonomy.apply(parse_file(&json, &path.display().to_string()));
+
+    if TAXONOMY.set(taxonomy).is_err() {
+        eprintln!("----> Taxonomy already in use, ignoring {}", path.display());
+    }
+}
+
+/// Restricts which canonical roles count, from `--roles` and
+/// `--exclude-roles`. Roles are given the same way jobs appear on IMDb and
+/// normalized, so `writer` and `created by` both mean `written by`.
+#[derive(Default)]
+pub struct RoleFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl RoleFilter {
+    /// Panics on a role the taxonomy doesn't know, which would otherwise
+    /// match nothing and quietly score every show 0.
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        let normalize = |roles: &[String]| -> Vec<String> {
+            if let Some(unknown) = roles.iter().find(|r| !taxonomy().knows(r)) {
+                panic!(
+                    "Unknown role {unknown}, expected one of: {}",
+                    taxonomy().role_names().join(", ")
+                );
+            }
+            let mut normalized: Vec<String> = vec![];
+            for role in roles.iter().map(|r| normalize_job(r).to_string()) {
+                if !normalized.contains(&role) {
+                    normalized.push(role);
+                }
+            }
+            normalized
+        };
+        Self {
+            include: normalize(include),
+            exclude: normalize(exclude),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.include.is_empty() || !self.exclude.is_empty()
+    }
+
+    pub fn allows(&self, job: &str) -> bool {
+        let role = normalize_job(job);
+        (self.include.is_empty() || self.include.iter().any(|r| r == role))
+            && !self.exclude.iter().any(|r| r == role)
+    }
+
+    pub fn include(&self) -> &[String] {
+        &self.include
+    }
+
+    pub fn exclude(&self) -> &[String] {
+        &self.exclude
+    }
+}
+
+impl fmt::Display for RoleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.include.is_empty() {
+            write!(f, "all roles")?;
+        } else {
+            write!(f, "{}", self.include.join(", "))?;
+        }
+        if !self.exclude.is_empty() {
+            write!(f, " except {}", self.exclude.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+
+use console::style;
+use rusqlite::Connection;
+
+use super::{
+    compare::{score_show_affinity, ShowAffinity},
+    filter::ShowMeta,
+    franchise::{franchise_of, FranchiseMode},
+    rank,
+    render::configure_styling,
+    seeds::find_series,
+    show_tree::{ShowPeepRecord, ShowRecord},
+    taxonomy::RoleFilter,
+    Ranking, SearchOptions,
+};
+
+/// Why a person does or doesn't count on one side of an overlap.
+fn peep_reason(peep: &ShowPeepRecord, show: &ShowRecord, roles: &RoleFilter) -> String {
+    let jobs = peep.jobs.join(", ");
+    if !peep.focused {
+        return format!("{jobs}: outside the role focus ({roles})");
+    }
+
+    let share = if peep.episode_share < 1.0 {
+        format!(
+            " × {:.2} for {}/{} eps",
+            peep.episode_share,
+            peep.episode_count,
+            show.episodes.len()
+        )
+    } else {
+        String::new()
+    };
+    if peep.stylistic {
+        format!(
+            "{jobs} → {} weight {}{share}, stylistic",
+            peep.role, peep.role_weight
+        )
+    } else {
+        format!(
+            "{jobs} → {} weight {}, an ignored job so not stylistic",
+            peep.role, peep.role_weight
+        )
+    }
+}
+
+/// How much `--not` took off a result, when it took anything.
+fn not_penalty(out: &mut impl Write, affinity: &ShowAffinity) {
+    let penalty = affinity.base_score - affinity.score;
+    if penalty > 0.0 {
+        writeln!(
+            out,
+            "  --not took {penalty:.2} off its {:.2}",
+            affinity.base_score
+        )
+        .unwrap();
+    }
+}
+
+/// Explains what happened to one show in a search: whether it was ever
+/// linked to the seeds, which seed people it shares and whether each of them
+/// counted, and where it ended up. The candidate is a tconst or a title, and
+/// an episode stands for its series.
+pub fn why_not(titles: Vec<String>, candidate: String, options: SearchOptions) {
+    let db = Connection::open(Path::new("caterer.db")).expect("can create db");
+    let Some(candidate_id) = find_series(&db, &candidate) else {
+        eprintln!("No show with the tconst or title {candidate}");
+        return;
+    };
+
+    let Ranking {
+        source,
+        roles,
+        seeds,
+        negatives,
+        seen,
+        staff,
+        linked,
+        hydrated,
+        scorer,
+        affinities,
+        ..
+    } = rank(titles, &options);
+
+    configure_styling(options.color, io::stdout().is_terminal());
+    let mut out = io::stdout().lock();
+
+    if seeds.iter().any(|s| s.id == candidate_id) {
+        writeln!(out, "tt{candidate_id:07} is one of the seeds").unwrap();
+        return;
+    }
+    if negatives.iter().any(|s| s.id == candidate_id) {
+        writeln!(out, "tt{candidate_id:07} is one of the --not seeds").unwrap();
+        return;
+    }
+
+    let record = source.fetch_show_record(candidate_id, &roles);
+    writeln!(
+        out,
+        "----> Why not {} ({})?",
+        style(&record.title).bold(),
+        record.start_year
+    )
+    .unwrap();
+
+    let filtered = !options.filter.allows(&ShowMeta::from(&record));
+    match linked.iter().position(|l| l.id == candidate_id) {
+        None if seen.binary_search(&candidate_id).is_ok() => {
+            writeln!(out, "✗ Already seen, so never recommended")
+        }
+        None if filtered => writeln!(out, "✗ Filtered out: not {}", options.filter),
+        None => writeln!(
+            out,
+            "✗ Never linked: none of the {} stylistic seed staff are credited on it",
+            staff.len()
+        ),
+        Some(i) if i >= hydrated => writeln!(
+            out,
+            "✗ Linked through {} seed staff, but pruned before scoring: pre-score {:.1} ranked {} of {} and only the top {hydrated} are kept (--max-candidates)",
+            linked[i].shared,
+            linked[i].weight,
+            i + 1,
+            linked.len(),
+        ),
+        Some(i) => writeln!(
+            out,
+            "✓ Linked through {} seed staff, pre-score {:.1} ranked {} of {}",
+            linked[i].shared,
+            linked[i].weight,
+            i + 1,
+            linked.len(),
+        ),
+    }
+    .unwrap();
+
+    writeln!(out, "\n{}", style("Shared people:").bold()).unwrap();
+    let mut shared_any = false;
+    for seed in &seeds {
+        let mut shared: Vec<_> = seed
+            .peeps
+            .values()
+            .filter_map(|p| record.peeps.get(&p.id).map(|c| (p, c)))
+            .collect();
+        shared.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+
+        for (seed_peep, candidate_peep) in shared {
+            shared_any = true;
+            let verdict = if !seed_peep.focused || !candidate_peep.focused {
+                style("doesn't count").red()
+            } else if seed_peep.stylistic {
+                style("links and scores").green()
+            } else {
+                style("scores, but can't link").yellow()
+            };
+            writeln!(
+                out,
+                "  • {} [{}] {verdict}\n      seed: {}\n      candidate: {}",
+                seed_peep.name,
+                seed.title,
+                peep_reason(seed_peep, seed, &roles),
+                peep_reason(candidate_peep, &record, &roles),
+            )
+            .unwrap();
+        }
+    }
+    if !shared_any {
+        writeln!(out, "  None of the people on the seeds worked on it").unwrap();
+    }
+
+    writeln!(out).unwrap();
+    let franchise = (options.franchise != FranchiseMode::Keep)
+        .then(|| franchise_of(&seeds, &record))
+        .flatten();
+    let affinity = score_show_affinity(
+        &seeds,
+        &negatives,
+        record,
+        scorer.as_ref(),
+        &options.recency,
+        options.genre_weight,
+        true,
+    );
+    let scored = affinities.iter().position(|a| a.show.id == candidate_id);
+    let rank = match scored {
+        Some(i) => i + 1,
+        None => {
+            writeln!(
+                out,
+                "{}",
+                style("It isn't in the results, this is what it would have got:").dim()
+            )
+            .unwrap();
+            affinities
+                .iter()
+                .filter(|a| a.score > affinity.score)
+                .count()
+                + 1
+        }
+    };
+
+    writeln!(
+        out,
+        "Score {:.2}, rank {rank} of {} results",
+        affinity.score,
+        affinities.len()
+    )
+    .unwrap();
+    if let Some(explanation) = &affinity.explanation {
+        for term in &explanation.terms {
+            writeln!(out, "  + {:>12.2}  {}", term.value, term.label).unwrap();
+        }
+        for multiplier in &explanation.multipliers {
+            writeln!(out, "  × {:>12.2}  {}", multiplier.value, multiplier.label).unwrap();
+        }
+        for penalty in &explanation.penalties {
+            writeln!(out, "  − {:>12.2}  {}", penalty.value, penalty.label).unwrap();
+        }
+    }
+
+    let grouped_under = affinities
+        .iter()
+        .find(|a| a.franchise.iter().any(|s| s.id == candidate_id));
+    if let Some(seed) = franchise.filter(|_| scored.is_none()) {
+        match grouped_under {
+            Some(group) => writeln!(
+                out,
+                "✗ Same franchise as {}, so it's grouped under {}",
+                seed.title, group.show.title
+            ),
+            None => writeln!(
+                out,
+                "✗ Same franchise as {}, removed by --hide-franchise",
+                seed.title
+            ),
+        }
+        .unwrap();
+    } else if affinity.base_score <= 0.0 {
+        writeln!(
+            out,
+            "✗ Nothing it shares with the seeds counts, so it scores nothing"
+        )
+        .unwrap();
+    } else if affinity.score < options.min_score {
+        writeln!(out, "✗ Below --min-score {}", options.min_score).unwrap();
+        not_penalty(&mut out, &affinity);
+    } else if rank <= options.offset || rank > options.offset + options.limit {
+        writeln!(
+            out,
+            "✗ Outside the printed results, which show ranks {} to {} (--offset/--limit)",
+            options.offset + 1,
+            options.offset + options.limit
+        )
+        .unwrap();
+        not_penalty(&mut out, &affinity);
+    } else if scored.is_some() {
+        writeln!(out, "✓ It's in the printed results").unwrap();
+    } else {
+        writeln!(out, "✓ It would have been in the printed results").unwrap();
+    }
+}